@@ -0,0 +1,93 @@
+//! Derivation of 1000 addresses of a BIP84 receive chain, `m/84'/0'/0'/0/*`.
+//!
+//! Run with `cargo bench --features rayon` to compare sequential and parallel
+//! derivation of the same range, the number of cores is printed first.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use wallet_bips::hd_wallet::b84::{Bip84, NativeSegwit};
+use wallet_bips::hd_wallet::{Chain, Client, Coin, Network};
+
+const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+const COUNT: u32 = 1_000;
+
+fn addresses(c: &mut Criterion) {
+    // m/84'/0'/0'/0
+    let account =
+        NativeSegwit::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin).unwrap();
+    let extended = NativeSegwit::derive_extended_key(account.privkey(), Chain::External).unwrap();
+    let privkey = extended.privkey().clone();
+    let encoded = privkey.to_string();
+
+    let mut group = c.benchmark_group("addresses");
+    group.throughput(Throughput::Elements(COUNT.into()));
+
+    group.bench_function(BenchmarkId::new("prepare_address", COUNT), |b| {
+        b.iter(|| {
+            for index in 0..COUNT {
+                black_box(
+                    NativeSegwit::prepare_address(&encoded, index, Network::Bitcoin).unwrap(),
+                );
+            }
+        });
+    });
+
+    group.bench_function(BenchmarkId::new("derive_addresses", COUNT), |b| {
+        b.iter(|| {
+            for address in NativeSegwit::derive_addresses(&privkey, 0..COUNT).unwrap() {
+                black_box(address.unwrap());
+            }
+        });
+    });
+
+    group.finish();
+}
+
+#[cfg(feature = "rayon")]
+fn sequential_vs_rayon(c: &mut Criterion) {
+    use rayon::iter::ParallelIterator;
+
+    let cores = std::thread::available_parallelism().map_or(1, usize::from);
+    println!(
+        "{cores} cores available, rayon uses {} threads",
+        rayon::current_num_threads()
+    );
+
+    let account =
+        NativeSegwit::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin).unwrap();
+    let extended = NativeSegwit::derive_extended_key(account.privkey(), Chain::External).unwrap();
+    let privkey = extended.privkey().clone();
+
+    let mut group = c.benchmark_group(format!("sequential_vs_rayon_{cores}_cores"));
+    group.throughput(Throughput::Elements(COUNT.into()));
+
+    group.bench_function(BenchmarkId::new("sequential", COUNT), |b| {
+        b.iter(|| {
+            NativeSegwit::derive_addresses(&privkey, 0..COUNT)
+                .unwrap()
+                .for_each(|address| {
+                    black_box(address.unwrap());
+                });
+        });
+    });
+
+    group.bench_function(BenchmarkId::new("rayon", COUNT), |b| {
+        b.iter(|| {
+            NativeSegwit::derive_addresses(&privkey, 0..COUNT)
+                .unwrap()
+                .into_par_iter()
+                .for_each(|address| {
+                    black_box(address.unwrap());
+                });
+        });
+    });
+
+    group.finish();
+}
+
+#[cfg(feature = "rayon")]
+criterion_group!(benches, addresses, sequential_vs_rayon);
+#[cfg(not(feature = "rayon"))]
+criterion_group!(benches, addresses);
+criterion_main!(benches);
@@ -1,3 +1,4 @@
+use crate::hd_wallet::Network;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +9,65 @@ pub enum WalletBipError {
     #[error("Could not split mnemonic: {0}")]
     SplitMnemonic(String),
 
+    #[error("Invalid entropy: {0}")]
+    InvalidEntropy(String),
+
+    #[error("Not enough entropy: {collected} bits collected, {needed} needed")]
+    InsufficientEntropy { needed: usize, collected: usize },
+
+    #[error("Could not recover mnemonic: {0}")]
+    RecoverMnemonic(String),
+
+    #[error("Too many hidden words: {hidden} hidden, at most {max} can be recovered")]
+    TooManyHiddenWords { hidden: usize, max: usize },
+
+    #[error("Invalid SLIP-39 share: {0}")]
+    Slip39(String),
+
+    #[error("Invalid derivation path: {0}")]
+    InvalidPath(String),
+
+    #[error("Invalid coin: {0}")]
+    InvalidCoin(String),
+
+    #[error("Invalid descriptor: {0}")]
+    Descriptor(String),
+
+    /// Test networks share their key versions, a key of any of them is
+    /// reported as [`Network::Testnet`].
+    #[error("Network mismatch: expected {expected} key, found {actual} key")]
+    NetworkMismatch { expected: Network, actual: Network },
+
+    #[error("Cannot derive hardened child from public key: {0}")]
+    HardenedFromPublic(String),
+
+    #[error("Key depth must be {expected}, found {actual}")]
+    InvalidDepth { expected: u8, actual: u8 },
+
+    #[error("Invalid hex character at position {position}")]
+    InvalidHex { position: usize },
+
+    #[error("Invalid checksum: {0}")]
+    InvalidChecksum(String),
+
+    #[error("Unknown Electrum seed version, not a standard or segwit seed")]
+    UnknownSeedVersion,
+
+    #[error("Unknown word {word:?} at index {index}")]
+    UnknownWord { index: usize, word: String },
+
+    #[error("Key origin mismatch: {0}")]
+    OriginMismatch(String),
+
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
+    #[error("Invalid multisig: {0}")]
+    InvalidMultisig(String),
+
+    #[error(transparent)]
+    EncodeAddress(#[from] bech32::segwit::EncodeError),
+
     #[error(transparent)]
     Crypto(#[from] bip32::Error),
 
@@ -15,5 +75,5 @@ pub enum WalletBipError {
     WriteOutput(#[from] std::fmt::Error),
 
     #[error(transparent)]
-    Unexpected(#[from] eyre::Report),
+    ReadInput(#[from] std::io::Error),
 }
@@ -1,7 +1,81 @@
+use crate::errors::WalletBipError;
+use crate::hd_wallet::Network;
 use crate::hex;
 use bip32::secp256k1::ecdsa::{SigningKey, VerifyingKey};
-use bip32::{ExtendedPrivateKey, ExtendedPublicKey};
+use bip32::{ExtendedPrivateKey, ExtendedPublicKey, PublicKey};
+use core::fmt;
 
+/// The output script an address pays to.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum ScriptType {
+    /// Legacy pay to public key hash, `1…` (BIP44).
+    P2pkh,
+
+    /// Nested segwit pay to witness public key hash wrapped in pay to script
+    /// hash, `3…` (BIP49).
+    P2shP2wpkh,
+
+    /// Native segwit pay to witness public key hash, `bc1q…` (BIP84).
+    P2wpkh,
+
+    /// Taproot single key pay to taproot, `bc1p…` (BIP86).
+    P2tr,
+
+    /// Ethereum and other EVM chains account, EIP-55 checksummed `0x…`.
+    Evm,
+}
+
+/// The version bytes and the segwit human readable part the addresses and WIF
+/// private keys of a coin are encoded with.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct AddressParams {
+    p2pkh: u8,
+    p2sh: u8,
+    wif: u8,
+    hrp: Option<&'static str>,
+}
+
+impl AddressParams {
+    pub const BITCOIN: AddressParams = AddressParams::new(0x00, 0x05, 0x80, Some("bc"));
+
+    /// Shared by testnet and signet.
+    pub const BITCOIN_TESTNET: AddressParams = AddressParams::new(0x6f, 0xc4, 0xef, Some("tb"));
+
+    pub const BITCOIN_REGTEST: AddressParams = AddressParams::new(0x6f, 0xc4, 0xef, Some("bcrt"));
+
+    /// `hrp` is `None` for coins without segwit addresses.
+    #[must_use]
+    pub const fn new(p2pkh: u8, p2sh: u8, wif: u8, hrp: Option<&'static str>) -> Self {
+        Self {
+            p2pkh,
+            p2sh,
+            wif,
+            hrp,
+        }
+    }
+
+    /// Whether the coin has segwit addresses.
+    #[must_use]
+    pub fn is_segwit(&self) -> bool {
+        self.hrp.is_some()
+    }
+
+    fn hrp(&self) -> crate::Result<bech32::Hrp> {
+        let hrp = self.hrp.ok_or_else(|| {
+            WalletBipError::InvalidCoin("the coin has no segwit addresses".to_string())
+        })?;
+
+        bech32::Hrp::parse(hrp)
+            .map_err(|_| WalletBipError::InvalidCoin(format!("{hrp} is not a valid bech32 prefix")))
+    }
+}
+
+/// An address with its public and private key, `privkey` is in WIF, or hex
+/// for EVM accounts.
+///
+/// Displayed as the address. With the `serde` feature it serializes as
+/// `{"hash": "1P9Q…", "pubkey": "0231…", "secret": {"privkey": "L4Nz…"}}`,
+/// watch-only exports drop `secret`.
 pub struct Address {
     pub(crate) hash: String,
     pub(crate) pubkey: String,
@@ -9,54 +83,374 @@ pub struct Address {
 }
 
 impl Address {
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be encoded.
     pub fn new(
         pubkey: &ExtendedPublicKey<VerifyingKey>,
         privkey: &ExtendedPrivateKey<SigningKey>,
+        script_type: ScriptType,
+        network: Network,
     ) -> crate::Result<Self> {
-        let wif = |privkey: &[u8; 32]| {
-            use sha2::{Digest, Sha256};
+        Self::from_keys(
+            pubkey.public_key(),
+            privkey.private_key(),
+            script_type,
+            network.address_params(),
+        )
+    }
+
+    /// The address itself, e.g. `bc1q…` or `0x…`.
+    #[must_use]
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The compressed public key, hex encoded.
+    #[must_use]
+    pub fn pubkey(&self) -> &str {
+        &self.pubkey
+    }
 
+    #[must_use]
+    pub fn privkey(&self) -> &str {
+        &self.privkey
+    }
+
+    /// Drops the private key.
+    #[must_use]
+    pub fn watch_only(self) -> WatchOnlyAddress {
+        WatchOnlyAddress {
+            hash: self.hash,
+            pubkey: self.pubkey,
+        }
+    }
+
+    pub(crate) fn from_keys(
+        pubkey: &VerifyingKey,
+        privkey: &SigningKey,
+        script_type: ScriptType,
+        params: AddressParams,
+    ) -> crate::Result<Self> {
+        let wif = |privkey: &[u8; 32]| {
             let mut payload = [0u8; 34];
-            payload[0] = 0x80;
+            payload[0] = params.wif;
             payload[1..33].copy_from_slice(privkey);
             payload[33] = 0x01;
 
-            let checksum = &Sha256::digest(Sha256::digest(payload))[..4];
+            base58check(&payload)
+        };
+
+        let WatchOnlyAddress { hash, pubkey } =
+            WatchOnlyAddress::from_key(pubkey, script_type, params)?;
+        let privkey = match script_type {
+            ScriptType::Evm => hex::encode(&privkey.to_bytes(), true)?,
+            _ => wif(&privkey.to_bytes().into()),
+        };
 
-            let mut result = [0u8; 38];
+        Ok(Self {
+            hash,
+            pubkey,
+            privkey,
+        })
+    }
+}
 
-            result[..34].copy_from_slice(&payload);
-            result[34..].copy_from_slice(checksum);
+/// An address derived from a public key only, it has no private part.
+///
+/// Displayed as the address. With the `serde` feature it serializes as
+/// `{"hash": "1P9Q…", "pubkey": "0231…"}`.
+pub struct WatchOnlyAddress {
+    pub(crate) hash: String,
+    pub(crate) pubkey: String,
+}
 
-            bs58::encode(result).into_string()
-        };
+impl WatchOnlyAddress {
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be encoded.
+    pub fn new(
+        pubkey: &ExtendedPublicKey<VerifyingKey>,
+        script_type: ScriptType,
+        network: Network,
+    ) -> crate::Result<Self> {
+        Self::from_key(pubkey.public_key(), script_type, network.address_params())
+    }
 
-        let p2pkh = |pubkey: &[u8; 33]| {
-            use ripemd::Ripemd160;
-            use sha2::{Digest, Sha256};
+    /// The address itself, e.g. `bc1q…` or `0x…`.
+    #[must_use]
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The compressed public key, hex encoded.
+    #[must_use]
+    pub fn pubkey(&self) -> &str {
+        &self.pubkey
+    }
 
+    pub(crate) fn from_key(
+        pubkey: &VerifyingKey,
+        script_type: ScriptType,
+        params: AddressParams,
+    ) -> crate::Result<Self> {
+        let p2pkh = |pubkey: &[u8; 33]| {
             let mut payload = [0u8; 21];
-            payload[0] = 0x00;
-            payload[1..].copy_from_slice(&Ripemd160::digest(Sha256::digest(pubkey))[..20]);
+            payload[0] = params.p2pkh;
+            payload[1..].copy_from_slice(&hash160(pubkey));
+
+            base58check(&payload)
+        };
 
-            let checksum = &Sha256::digest(Sha256::digest(payload))[..4];
+        let p2sh_p2wpkh = |pubkey: &[u8; 33]| {
+            // OP_0 <20 byte public key hash>
+            let mut redeem_script = [0u8; 22];
+            redeem_script[0] = 0x00;
+            redeem_script[1] = 0x14;
+            redeem_script[2..].copy_from_slice(&hash160(pubkey));
 
-            let mut result = [0u8; 25];
+            p2sh(&redeem_script, params)
+        };
 
-            result[..21].copy_from_slice(&payload);
-            result[21..].copy_from_slice(checksum);
+        let segwit_v0 = |pubkey: &[u8; 33]| {
+            bech32::segwit::encode_v0(params.hrp()?, &hash160(pubkey)).map_err(WalletBipError::from)
+        };
 
-            bs58::encode(result).into_string()
+        let segwit_v1 = |pubkey: &VerifyingKey| {
+            bech32::segwit::encode_v1(params.hrp()?, &taproot_output_key(pubkey)?)
+                .map_err(WalletBipError::from)
         };
 
-        let hash = p2pkh(&pubkey.to_bytes());
-        let privkey = wif(&privkey.to_bytes());
-        let pubkey = hex::encode(&pubkey.to_bytes(), false)?;
+        let bytes = PublicKey::to_bytes(pubkey);
 
-        Ok(Self {
-            hash,
-            pubkey,
-            privkey,
-        })
+        let hash = match script_type {
+            ScriptType::P2pkh => p2pkh(&bytes),
+            ScriptType::P2shP2wpkh if !params.is_segwit() => {
+                return Err(WalletBipError::InvalidCoin(
+                    "the coin has no segwit addresses".to_string(),
+                ));
+            }
+            ScriptType::P2shP2wpkh => p2sh_p2wpkh(&bytes),
+            ScriptType::P2wpkh => segwit_v0(&bytes)?,
+            ScriptType::P2tr => segwit_v1(pubkey)?,
+            ScriptType::Evm => evm(pubkey)?,
+        };
+        let pubkey = hex::encode(&bytes, false)?;
+
+        Ok(Self { hash, pubkey })
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.hash)
+    }
+}
+
+impl fmt::Display for WatchOnlyAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.hash)
+    }
+}
+
+/// Pay to script hash address of a redeem script.
+pub(crate) fn p2sh(redeem_script: &[u8], params: AddressParams) -> String {
+    let mut payload = [0u8; 21];
+    payload[0] = params.p2sh;
+    payload[1..].copy_from_slice(&hash160(redeem_script));
+
+    base58check(&payload)
+}
+
+/// Native segwit pay to witness script hash address of a witness script.
+pub(crate) fn p2wsh(witness_script: &[u8], params: AddressParams) -> crate::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    bech32::segwit::encode_v0(params.hrp()?, &Sha256::digest(witness_script))
+        .map_err(WalletBipError::from)
+}
+
+/// Nested segwit pay to witness script hash wrapped in pay to script hash
+/// address of a witness script.
+pub(crate) fn p2sh_p2wsh(witness_script: &[u8], params: AddressParams) -> String {
+    use sha2::{Digest, Sha256};
+
+    // OP_0 <32 byte script hash>
+    let mut redeem_script = [0u8; 34];
+    redeem_script[0] = 0x00;
+    redeem_script[1] = 0x20;
+    redeem_script[2..].copy_from_slice(&Sha256::digest(witness_script));
+
+    p2sh(&redeem_script, params)
+}
+
+/// Bare `OP_CHECKMULTISIG` script requiring `threshold` signatures of the
+/// public keys, in the given order.
+pub(crate) fn multisig_script(threshold: usize, pubkeys: &[[u8; 33]]) -> crate::Result<Vec<u8>> {
+    const OP_1: u8 = 0x51;
+    const OP_CHECKMULTISIG: u8 = 0xae;
+
+    /// The maximum number of public keys `OP_CHECKMULTISIG` accepts.
+    const MAX_PUBKEYS: u8 = 20;
+
+    // 1 to 16 are pushed with their own opcode, larger numbers as one byte.
+    let number = |n: usize| {
+        u8::try_from(n)
+            .ok()
+            .filter(|n| (1..=MAX_PUBKEYS).contains(n))
+            .map(|n| {
+                if n <= 16 {
+                    vec![OP_1 + n - 1]
+                } else {
+                    vec![0x01, n]
+                }
+            })
+    };
+
+    let (Some(op_threshold), Some(op_count)) = (number(threshold), number(pubkeys.len())) else {
+        return Err(WalletBipError::InvalidMultisig(format!(
+            "{threshold} of {} is out of range",
+            pubkeys.len()
+        )));
+    };
+
+    if threshold > pubkeys.len() {
+        return Err(WalletBipError::InvalidMultisig(format!(
+            "threshold {threshold} exceeds {} keys",
+            pubkeys.len()
+        )));
+    }
+
+    let mut script = Vec::with_capacity(5 + pubkeys.len() * 34);
+    script.extend(op_threshold);
+
+    for pubkey in pubkeys {
+        script.push(0x21);
+        script.extend_from_slice(pubkey);
+    }
+
+    script.extend(op_count);
+    script.push(OP_CHECKMULTISIG);
+
+    Ok(script)
+}
+
+/// Keccak-256 of the uncompressed public key without its `0x04` tag, the
+/// address is the last 20 bytes of the hash.
+fn evm(pubkey: &VerifyingKey) -> crate::Result<String> {
+    use sha3::{Digest, Keccak256};
+
+    let encoded = pubkey.to_encoded_point(false);
+    let hash = Keccak256::digest(&encoded.as_bytes()[1..]);
+
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+
+    eip55(&address)
+}
+
+/// Mixed-case checksum encoding of an EVM address (EIP-55).
+fn eip55(address: &[u8; 20]) -> crate::Result<String> {
+    use sha3::{Digest, Keccak256};
+
+    let lower = hex::encode(address, false)?;
+    let hash = Keccak256::digest(lower.as_bytes());
+
+    let mut result = String::with_capacity(2 + lower.len());
+    result.push_str("0x");
+
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+
+        if nibble >= 8 {
+            result.push(c.to_ascii_uppercase());
+        } else {
+            result.push(c);
+        }
+    }
+
+    Ok(result)
+}
+
+fn hash160(bytes: &[u8]) -> [u8; 20] {
+    use ripemd::Ripemd160;
+    use sha2::{Digest, Sha256};
+
+    Ripemd160::digest(Sha256::digest(bytes)).into()
+}
+
+/// Tweaks the internal key with an unspendable script path as BIP86 requires
+/// and returns the x-only output key.
+fn taproot_output_key(pubkey: &VerifyingKey) -> crate::Result<[u8; 32]> {
+    use bip32::secp256k1::elliptic_curve::PrimeField;
+    use bip32::secp256k1::elliptic_curve::sec1::{Tag, ToEncodedPoint};
+    use bip32::secp256k1::{ProjectivePoint, Scalar};
+    use sha2::{Digest, Sha256};
+
+    let encoded = pubkey.as_affine().to_encoded_point(true);
+    let x = encoded
+        .x()
+        .ok_or_else(|| WalletBipError::InvalidKey("public key is the identity".to_string()))?;
+
+    // The internal key is the point with the even y coordinate.
+    let mut internal = ProjectivePoint::from(*pubkey.as_affine());
+    if encoded.tag() == Tag::CompressedOddY {
+        internal = -internal;
+    }
+
+    let tag = Sha256::digest(b"TapTweak");
+    let tweak = Sha256::new()
+        .chain_update(tag)
+        .chain_update(tag)
+        .chain_update(x)
+        .finalize();
+
+    let tweak = Option::<Scalar>::from(Scalar::from_repr(tweak))
+        .ok_or_else(|| WalletBipError::InvalidKey("taproot tweak is out of range".to_string()))?;
+
+    let output = (internal + ProjectivePoint::GENERATOR * tweak)
+        .to_affine()
+        .to_encoded_point(true);
+
+    output
+        .x()
+        .map(|x| (*x).into())
+        .ok_or_else(|| WalletBipError::InvalidKey("taproot output key is the identity".to_string()))
+}
+
+fn base58check(payload: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    let checksum = &Sha256::digest(Sha256::digest(payload))[..4];
+
+    let mut result = Vec::with_capacity(payload.len() + checksum.len());
+
+    result.extend_from_slice(payload);
+    result.extend_from_slice(checksum);
+
+    bs58::encode(result).into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")]
+    #[case("0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359")]
+    #[case("0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB")]
+    #[case("0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb")]
+    fn test_eip55(#[case] expected: &str) {
+        let address: [u8; 20] = hex::decode(&expected.to_lowercase())
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        assert_eq!(eip55(&address).unwrap(), expected);
     }
 }
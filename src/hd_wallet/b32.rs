@@ -1,17 +1,37 @@
 use crate::Result;
-use crate::errors::WalletBipError;
-use crate::hd_wallet::{Client, ExtendedPubPrivKey};
-use bip32::{ChildNumber, XPrv};
-use core::str::FromStr;
-use eyre::eyre;
+use crate::hd_wallet::{
+    Client, Coin, ExtendedPrivKey, ExtendedPubPrivKey, Network, ScriptType, check_depth,
+};
+use bip32::ChildNumber;
 
 pub trait Bip32: Client {
     const ROOT_KEY_DEPTH: u8 = 0;
 
-    fn prepare_extended_key(root_key: &str) -> Result<ExtendedPubPrivKey>;
+    /// Derives the client's extended keys from a root key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not a root key or the derivation fails.
+    fn derive_extended_key(root: &ExtendedPrivKey) -> Result<ExtendedPubPrivKey>;
+
+    /// Parses the root key, see [`Bip32::derive_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not a root key or the derivation fails.
+    fn prepare_extended_key(root_key: &str, network: Network) -> Result<ExtendedPubPrivKey> {
+        Self::derive_extended_key(&ExtendedPrivKey::parse(root_key, network)?)
+    }
+}
+
+/// Checks that a key is the root key a client derives from.
+fn check_root<C: Bip32>(root: &ExtendedPrivKey) -> Result<()> {
+    check_depth(root.depth(), C::ROOT_KEY_DEPTH)
 }
 
-struct BitcoinCore;
+/// Bitcoin Core before descriptor wallets, addresses at `m/0'/0'/i'`.
+pub struct BitcoinCore;
 
 impl Client for BitcoinCore {
     const EXTENDED_KEY_DEPTH: u8 = 2;
@@ -20,27 +40,21 @@ impl Client for BitcoinCore {
 }
 
 impl Bip32 for BitcoinCore {
-    fn prepare_extended_key(root_key: &str) -> Result<ExtendedPubPrivKey> {
-        let root = XPrv::from_str(root_key)?;
-
-        if root.attrs().depth != Self::ROOT_KEY_DEPTH {
-            return Err(WalletBipError::Unexpected(eyre!(
-                "Key depth must be {}",
-                Self::ROOT_KEY_DEPTH
-            )));
-        }
+    fn derive_extended_key(root: &ExtendedPrivKey) -> Result<ExtendedPubPrivKey> {
+        check_root::<Self>(root)?;
 
         // m/0'/0'
         let privkey = root
             .derive_child(ChildNumber::new(0, true)?)?
-            .derive_child(ChildNumber::new(0, true)?)?;
-        let pubkey = privkey.public_key();
+            .derive_child(ChildNumber::new(0, true)?)?
+            .with_script_type(ScriptType::P2pkh);
 
-        Ok(ExtendedPubPrivKey::new(&pubkey, &privkey))
+        Ok(ExtendedPubPrivKey::from(privkey))
     }
 }
 
-struct Multibit;
+/// Multibit HD, addresses at `m/0'/0/i`.
+pub struct Multibit;
 
 impl Client for Multibit {
     const EXTENDED_KEY_DEPTH: u8 = 2;
@@ -49,26 +63,22 @@ impl Client for Multibit {
 }
 
 impl Bip32 for Multibit {
-    fn prepare_extended_key(root_key: &str) -> Result<ExtendedPubPrivKey> {
-        let root = XPrv::from_str(root_key)?;
-
-        if root.attrs().depth != Self::ROOT_KEY_DEPTH {
-            return Err(WalletBipError::Unexpected(eyre!(
-                "Key depth must be {}",
-                Self::ROOT_KEY_DEPTH
-            )));
-        }
+    fn derive_extended_key(root: &ExtendedPrivKey) -> Result<ExtendedPubPrivKey> {
+        check_root::<Self>(root)?;
 
         // m/0'/0
         let privkey = root
             .derive_child(ChildNumber::new(0, true)?)?
-            .derive_child(ChildNumber::new(0, false)?)?;
-        let pubkey = privkey.public_key();
+            .derive_child(ChildNumber::new(0, false)?)?
+            .with_script_type(ScriptType::P2pkh);
 
-        Ok(ExtendedPubPrivKey::new(&pubkey, &privkey))
+        Ok(ExtendedPubPrivKey::from(privkey))
     }
 }
-struct BlockExplorer;
+
+/// Block explorers exporting a BIP44 account as a BIP32 key, addresses at
+/// `m/44'/0'/0'/i`.
+pub struct BlockExplorer;
 
 impl Client for BlockExplorer {
     const EXTENDED_KEY_DEPTH: u8 = 3;
@@ -77,30 +87,27 @@ impl Client for BlockExplorer {
 }
 
 impl Bip32 for BlockExplorer {
-    fn prepare_extended_key(root_key: &str) -> Result<ExtendedPubPrivKey> {
-        let root = XPrv::from_str(root_key)?;
-
-        if root.attrs().depth != Self::ROOT_KEY_DEPTH {
-            return Err(WalletBipError::Unexpected(eyre!(
-                "Key depth must be {}",
-                Self::ROOT_KEY_DEPTH
-            )));
-        }
+    fn derive_extended_key(root: &ExtendedPrivKey) -> Result<ExtendedPubPrivKey> {
+        check_root::<Self>(root)?;
 
         // m/44'/0'/0'
         let privkey = root
             .derive_child(ChildNumber::new(44, true)?)?
+            .derive_child(ChildNumber::new(
+                root.network().coin_type(Coin::BTC.coin_type()),
+                true,
+            )?)?
             .derive_child(ChildNumber::new(0, true)?)?
-            .derive_child(ChildNumber::new(0, true)?)?;
-        let pubkey = privkey.public_key();
+            .with_script_type(ScriptType::P2pkh);
 
-        Ok(ExtendedPubPrivKey::new(&pubkey, &privkey))
+        Ok(ExtendedPubPrivKey::from(privkey))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::errors::WalletBipError;
     use rstest::rstest;
 
     mod bitcoin_core {
@@ -110,14 +117,14 @@ mod tests {
         fn test_prepare_extended_key() {
             let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
 
-            let extended = BitcoinCore::prepare_extended_key(root).unwrap();
+            let extended = BitcoinCore::prepare_extended_key(root, Network::Bitcoin).unwrap();
 
             assert_eq!(
-                extended.privkey,
+                extended.privkey.to_string(),
                 "xprv9wfndKaiDKD8UKCVyYhDG5boquxEqEZD2Dr2CNxNDKLtZw3tqvJZ1DgFyqWqa2DPwSoApgDy7BdrG8YaxbHTdGMWMP5X2n957iBYPAfDKKy"
             );
             assert_eq!(
-                extended.pubkey,
+                extended.pubkey.to_string(),
                 "xpub6Af92q7c3gmRgoGy5aEDdDYYPwnjEhH4PSmczmMymessSjP3PTcoZ1zjq75AXGHTaRU7WMYWsw1Cdc1u5knfzChi8FJxv6ipPgPbbds27ns"
             );
         }
@@ -149,12 +156,37 @@ mod tests {
         ) {
             let extended = "xprv9wfndKaiDKD8UKCVyYhDG5boquxEqEZD2Dr2CNxNDKLtZw3tqvJZ1DgFyqWqa2DPwSoApgDy7BdrG8YaxbHTdGMWMP5X2n957iBYPAfDKKy";
 
-            let result = BitcoinCore::prepare_address(extended, index).unwrap();
+            let result = BitcoinCore::prepare_address(extended, index, Network::Bitcoin).unwrap();
 
             assert_eq!(result.hash, hash);
             assert_eq!(result.pubkey, pubkey);
             assert_eq!(result.privkey, privkey);
         }
+
+        #[test]
+        fn test_prepare_watch_only_address_hardened() {
+            let extended = "xpub6Af92q7c3gmRgoGy5aEDdDYYPwnjEhH4PSmczmMymessSjP3PTcoZ1zjq75AXGHTaRU7WMYWsw1Cdc1u5knfzChi8FJxv6ipPgPbbds27ns";
+
+            assert!(matches!(
+                BitcoinCore::prepare_watch_only_address(extended, 0, Network::Bitcoin),
+                Err(WalletBipError::HardenedFromPublic(_))
+            ));
+        }
+
+        #[test]
+        fn test_prepare_descriptors_hardened() {
+            let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
+
+            let extended = BitcoinCore::prepare_extended_key(root, Network::Bitcoin).unwrap();
+            let descriptors = BitcoinCore::prepare_descriptors(&extended).unwrap();
+
+            assert!(matches!(
+                descriptors.public(),
+                Err(WalletBipError::HardenedFromPublic(_))
+            ));
+            assert!(descriptors.private().starts_with("pkh(xprv"));
+            assert!(descriptors.private().contains("/*h)#"));
+        }
     }
 
     mod multibit {
@@ -164,14 +196,14 @@ mod tests {
         fn test_prepare_extended_key() {
             let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
 
-            let extended = Multibit::prepare_extended_key(root).unwrap();
+            let extended = Multibit::prepare_extended_key(root, Network::Bitcoin).unwrap();
 
             assert_eq!(
-                extended.privkey,
+                extended.privkey.to_string(),
                 "xprv9wfndKaZsegAGojErEykgw5Td6nW1qXkbkoVRHCFV2yPv6gNkTbKNpqrLfTXoi8HVVCPLcUNtQaWFao3ecE53qeYRcE5jVBCyCAiXiVW86y"
             );
             assert_eq!(
-                extended.pubkey,
+                extended.pubkey.to_string(),
                 "xpub6Af92q7Ti2ETVHohxGWm452CB8czRJFbxyj6Dfbs3NWNnu1XHzuZvdALBwqA8vjdkyfeDTnj2NDGs48xqfJJDR7zZT5JxUt17qnmmh4Govs"
             );
         }
@@ -203,12 +235,25 @@ mod tests {
         ) {
             let extended = "xprv9wfndKaZsegAGojErEykgw5Td6nW1qXkbkoVRHCFV2yPv6gNkTbKNpqrLfTXoi8HVVCPLcUNtQaWFao3ecE53qeYRcE5jVBCyCAiXiVW86y";
 
-            let result = Multibit::prepare_address(extended, index).unwrap();
+            let result = Multibit::prepare_address(extended, index, Network::Bitcoin).unwrap();
 
             assert_eq!(result.hash, hash);
             assert_eq!(result.pubkey, pubkey);
             assert_eq!(result.privkey, privkey);
         }
+
+        #[rstest]
+        #[case(0, "1Kz3Tq4u89vEk2RZN6EF888nsA289C5kMD")]
+        #[case(7, "13GEofuoFC3ATTfhQtuwbR3u1ZBRk4cDA7")]
+        #[case(18, "1KBHf6tUkiuxrfETmXduB6He2Dvgw9Ve2j")]
+        fn test_prepare_watch_only_addresses(#[case] index: u32, #[case] hash: &str) {
+            let extended = "xpub6Af92q7Ti2ETVHohxGWm452CB8czRJFbxyj6Dfbs3NWNnu1XHzuZvdALBwqA8vjdkyfeDTnj2NDGs48xqfJJDR7zZT5JxUt17qnmmh4Govs";
+
+            let result =
+                Multibit::prepare_watch_only_address(extended, index, Network::Bitcoin).unwrap();
+
+            assert_eq!(result.hash, hash);
+        }
     }
 
     mod block_explorer {
@@ -218,14 +263,14 @@ mod tests {
         fn test_prepare_extended_key() {
             let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
 
-            let extended = BlockExplorer::prepare_extended_key(root).unwrap();
+            let extended = BlockExplorer::prepare_extended_key(root, Network::Bitcoin).unwrap();
 
             assert_eq!(
-                extended.privkey,
+                extended.privkey.to_string(),
                 "xprv9zPECzuhYNLzQzEw3kacYkJyAcox4RCKLTYKyB59YpCrPdG3i9TQ5Tzm78LmpheejAPKy1JBKgDqSvouiqrirfVxVXoKhdmi5mVMEWGFr6S"
             );
             assert_eq!(
-                extended.pubkey,
+                extended.pubkey.to_string(),
                 "xpub6DNacWSbNjuHdUKQ9n7cutFhieeSTsvAhgTvmZUm79jqGRbCFgmedGKExRZHEfSzzCdxRTkXTGr34oRWruVGS8ie12imQJ4dhVCQAHWFstY"
             );
         }
@@ -257,11 +302,20 @@ mod tests {
         ) {
             let extended = "xprv9zPECzuhYNLzQzEw3kacYkJyAcox4RCKLTYKyB59YpCrPdG3i9TQ5Tzm78LmpheejAPKy1JBKgDqSvouiqrirfVxVXoKhdmi5mVMEWGFr6S";
 
-            let result = BlockExplorer::prepare_address(extended, index).unwrap();
+            let result = BlockExplorer::prepare_address(extended, index, Network::Bitcoin).unwrap();
 
             assert_eq!(result.hash, hash);
             assert_eq!(result.pubkey, pubkey);
             assert_eq!(result.privkey, privkey);
         }
+
+        #[test]
+        fn test_prepare_watch_only_address_invalid_depth() {
+            let extended = "xpub6Af92q7Ti2ETVHohxGWm452CB8czRJFbxyj6Dfbs3NWNnu1XHzuZvdALBwqA8vjdkyfeDTnj2NDGs48xqfJJDR7zZT5JxUt17qnmmh4Govs";
+
+            assert!(
+                BlockExplorer::prepare_watch_only_address(extended, 0, Network::Bitcoin).is_err()
+            );
+        }
     }
 }
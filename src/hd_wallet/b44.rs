@@ -1,34 +1,66 @@
 use crate::Result;
-use crate::errors::WalletBipError;
-use crate::hd_wallet::{Client, ExtendedPubPrivKey};
-use bip32::{ChildNumber, XPrv};
-use eyre::eyre;
-use std::str::FromStr;
+use crate::hd_wallet::{
+    Chain, Client, Coin, ExtendedPrivKey, ExtendedPubPrivKey, Network, ScriptType, derive_account,
+    derive_chain,
+};
 
 pub trait Bip44: Client {
-    const ROOT_KEY_DEPTH: u8 = 0;
+    /// Derives the account keys `m/44'/coin'/account'` from a root key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not a root key, the client cannot encode
+    /// addresses of the coin or the derivation fails.
+    fn derive_account_extended_key(
+        root: &ExtendedPrivKey,
+        coin: &Coin,
+        account: u32,
+    ) -> Result<ExtendedPubPrivKey> {
+        derive_account(root, 44, coin, account, Self::SCRIPT_TYPE)
+    }
+
+    /// Derives the keys of a chain of an account key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not an account key or the derivation
+    /// fails.
+    fn derive_extended_key(account: &ExtendedPrivKey, chain: Chain) -> Result<ExtendedPubPrivKey> {
+        derive_chain(account, chain, Self::SCRIPT_TYPE)
+    }
 
+    /// Parses the root key, see [`Bip44::derive_account_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not a root key, the client cannot encode addresses of the
+    /// coin or the derivation fails.
     fn prepare_account_extended_key(
         root_key: &str,
-        coin: Coin,
+        coin: &Coin,
         account: u32,
-    ) -> Result<ExtendedPubPrivKey>;
-
-    fn prepare_extended_key(account_key: &str, is_external: bool) -> Result<ExtendedPubPrivKey>;
-}
-
-#[derive(Debug, Hash, PartialEq, Eq)]
-pub enum Coin {
-    Btc,
-    Eth,
-}
+        network: Network,
+    ) -> Result<ExtendedPubPrivKey> {
+        Self::derive_account_extended_key(
+            &ExtendedPrivKey::parse(root_key, network)?,
+            coin,
+            account,
+        )
+    }
 
-impl From<Coin> for u32 {
-    fn from(coin: Coin) -> u32 {
-        match coin {
-            Coin::Btc => 0,
-            Coin::Eth => 60,
-        }
+    /// Parses the account key, see [`Bip44::derive_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not an account key or the derivation fails.
+    fn prepare_extended_key(
+        account_key: &str,
+        chain: Chain,
+        network: Network,
+    ) -> Result<ExtendedPubPrivKey> {
+        Self::derive_extended_key(&ExtendedPrivKey::parse(account_key, network)?, chain)
     }
 }
 
@@ -39,46 +71,26 @@ impl Client for BlockExplorer {
 
     const IS_HARDENED_ADDRESSES: bool = false;
 }
-impl Bip44 for BlockExplorer {
-    fn prepare_account_extended_key(
-        root_key: &str,
-        coin: Coin,
-        account: u32,
-    ) -> Result<ExtendedPubPrivKey> {
-        let root = XPrv::from_str(root_key)?;
-
-        if root.attrs().depth != Self::ROOT_KEY_DEPTH {
-            return Err(WalletBipError::Unexpected(eyre!(
-                "Key depth must be {}",
-                Self::ROOT_KEY_DEPTH
-            )));
-        }
 
-        // m/44'/coin'/account'
-        let privkey = root
-            .derive_child(ChildNumber::new(44, true)?)?
-            .derive_child(ChildNumber::new(coin as u32, true)?)?
-            .derive_child(ChildNumber::new(account, true)?)?;
-        let pubkey = privkey.public_key();
+impl Bip44 for BlockExplorer {}
 
-        Ok(ExtendedPubPrivKey::new(&pubkey, &privkey))
-    }
+/// Ethereum account as derived by most EVM wallets, e.g. Metamask or Ledger.
+pub struct Ethereum;
 
-    fn prepare_extended_key(account_key: &str, is_external: bool) -> Result<ExtendedPubPrivKey> {
-        let account_extended = XPrv::from_str(account_key)?;
+impl Client for Ethereum {
+    const EXTENDED_KEY_DEPTH: u8 = 4;
 
-        // m/44'/coin'/account'/is_external
-        let privkey =
-            account_extended.derive_child(ChildNumber::new(u32::from(is_external), false)?)?;
-        let pubkey = privkey.public_key();
+    const IS_HARDENED_ADDRESSES: bool = false;
 
-        Ok(ExtendedPubPrivKey::new(&pubkey, &privkey))
-    }
+    const SCRIPT_TYPE: ScriptType = ScriptType::Evm;
 }
 
+impl Bip44 for Ethereum {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::errors::WalletBipError;
 
     mod block_explorer {
         use super::*;
@@ -89,14 +101,15 @@ mod tests {
             let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
 
             let account_extended =
-                BlockExplorer::prepare_account_extended_key(root, Coin::Btc, 0).unwrap();
+                BlockExplorer::prepare_account_extended_key(root, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
 
             assert_eq!(
-                account_extended.privkey,
+                account_extended.privkey.to_string(),
                 "xprv9zPECzuhYNLzQzEw3kacYkJyAcox4RCKLTYKyB59YpCrPdG3i9TQ5Tzm78LmpheejAPKy1JBKgDqSvouiqrirfVxVXoKhdmi5mVMEWGFr6S"
             );
             assert_eq!(
-                account_extended.pubkey,
+                account_extended.pubkey.to_string(),
                 "xpub6DNacWSbNjuHdUKQ9n7cutFhieeSTsvAhgTvmZUm79jqGRbCFgmedGKExRZHEfSzzCdxRTkXTGr34oRWruVGS8ie12imQJ4dhVCQAHWFstY"
             );
         }
@@ -105,14 +118,19 @@ mod tests {
         fn test_prepare_extended_key() {
             let account_extended = "xprv9zPECzuhYNLzQzEw3kacYkJyAcox4RCKLTYKyB59YpCrPdG3i9TQ5Tzm78LmpheejAPKy1JBKgDqSvouiqrirfVxVXoKhdmi5mVMEWGFr6S";
 
-            let extended = BlockExplorer::prepare_extended_key(account_extended, false).unwrap();
+            let extended = BlockExplorer::prepare_extended_key(
+                account_extended,
+                Chain::External,
+                Network::Bitcoin,
+            )
+            .unwrap();
 
             assert_eq!(
-                extended.privkey,
+                extended.privkey.to_string(),
                 "xprvA1Nd3YgbqxvrcjZsCA96KsyPrLMK4TbPQq983aiN4k9Sx3DM5aKBnY7ejaasiCcqEwSbLP7QFnDJr2qxcjKhr6fPzQUGrGTS42T5QfQK9tL"
             );
             assert_eq!(
-                extended.pubkey,
+                extended.pubkey.to_string(),
                 "xpub6EMyT4DVgLV9qDeLJBg6h1v8QNBoTvKEn44iqy7yd5gRpqYVd7dSLLS8as4AW1Hz1JntmK1UtRihCejx2JJq81N1nbS1aUGmPcDGjKpt7ss"
             );
         }
@@ -144,11 +162,237 @@ mod tests {
         ) {
             let extended = "xprvA1Nd3YgbqxvrcjZsCA96KsyPrLMK4TbPQq983aiN4k9Sx3DM5aKBnY7ejaasiCcqEwSbLP7QFnDJr2qxcjKhr6fPzQUGrGTS42T5QfQK9tL";
 
-            let result = BlockExplorer::prepare_address(extended, index).unwrap();
+            let result = BlockExplorer::prepare_address(extended, index, Network::Bitcoin).unwrap();
 
             assert_eq!(result.hash, hash);
             assert_eq!(result.pubkey, pubkey);
             assert_eq!(result.privkey, privkey);
         }
+
+        #[test]
+        fn test_prepare_testnet_address() {
+            let root = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd";
+
+            let account_extended =
+                BlockExplorer::prepare_account_extended_key(root, &Coin::BTC, 0, Network::Testnet)
+                    .unwrap();
+
+            assert_eq!(
+                account_extended.privkey.to_string(),
+                "tprv8fPDJN9UQqg6pFsQsrVxTwHZmXLvHpfGGcsCA9rtnatUgVtBKxhtFeqiyaYKSWydunKpjhvgJf6PwTwgirwuCbFq8YKgpQiaVJf3JCrNmkR"
+            );
+
+            let extended =
+                BlockExplorer::derive_extended_key(&account_extended.privkey, Chain::External)
+                    .unwrap();
+            let result = BlockExplorer::derive_address(&extended.privkey, 0).unwrap();
+
+            assert_eq!(result.hash, "mkpZhYtJu2r87Js3pDiWJDmPte2NRZ8bJV");
+            assert_eq!(
+                result.pubkey,
+                "02a7451395735369f2ecdfc829c0f774e88ef1303dfe5b2f04dbaab30a535dfdd6"
+            );
+            assert_eq!(
+                result.privkey,
+                "cV6NTLu255SZ5iCNkVHezNGDH5qv6CanJpgBPqYgJU13NNKJhRs1"
+            );
+        }
+
+        #[test]
+        fn test_prepare_account_key_network_mismatch() {
+            let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
+
+            assert!(matches!(
+                BlockExplorer::prepare_account_extended_key(root, &Coin::BTC, 0, Network::Regtest),
+                Err(WalletBipError::NetworkMismatch { .. })
+            ));
+        }
+
+        #[rstest]
+        #[case("LTC", "LUWPbpM43E2p7ZSh8cyTBEkvpHmr3cB8Ez")]
+        #[case("DOGE", "DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC")]
+        fn test_prepare_coin_address(#[case] symbol: &str, #[case] hash: &str) {
+            use crate::hd_wallet::CoinRegistry;
+
+            let root = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+            let registry = CoinRegistry::default();
+            let coin = registry.by_symbol(symbol).unwrap();
+
+            let account =
+                BlockExplorer::prepare_account_extended_key(root, coin, 0, Network::Bitcoin)
+                    .unwrap();
+            let extended =
+                BlockExplorer::derive_extended_key(&account.privkey, Chain::External).unwrap();
+            let result = BlockExplorer::derive_address(&extended.privkey, 0).unwrap();
+
+            assert_eq!(result.hash, hash);
+            assert_eq!(
+                BlockExplorer::derive_watch_only_address(&extended.pubkey, 0)
+                    .unwrap()
+                    .hash,
+                hash
+            );
+        }
+
+        #[rstest]
+        #[case("SOL")]
+        #[case("ADA")]
+        #[case("ETH")]
+        fn test_prepare_account_key_unsupported_coin(#[case] symbol: &str) {
+            use crate::hd_wallet::CoinRegistry;
+
+            let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
+            let registry = CoinRegistry::default();
+            let coin = registry.by_symbol(symbol).unwrap();
+
+            assert!(matches!(
+                BlockExplorer::prepare_account_extended_key(root, coin, 0, Network::Bitcoin),
+                Err(WalletBipError::InvalidCoin(_))
+            ));
+        }
+
+        #[test]
+        fn test_prepare_descriptors() {
+            let root = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+            let account_extended =
+                BlockExplorer::prepare_account_extended_key(root, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
+
+            let descriptors = BlockExplorer::prepare_descriptors(&account_extended).unwrap();
+
+            assert_eq!(
+                descriptors.public().unwrap(),
+                "pkh([73c5da0a/44h/0h/0h]xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj/<0;1>/*)#kw28l7md"
+            );
+        }
+
+        #[rstest]
+        #[case(Chain::External, 0, "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA")]
+        #[case(Chain::External, 1, "1Ak8PffB2meyfYnbXZR9EGfLfFZVpzJvQP")]
+        #[case(Chain::Internal, 0, "1J3J6EvPrv8q6AC3VCjWV45Uf3nssNMRtH")]
+        #[case(Chain::Internal, 1, "13vKxXzHXXd8HquAYdpkJoi9ULVXUgfpS5")]
+        fn test_prepare_chain_addresses(
+            #[case] chain: Chain,
+            #[case] index: u32,
+            #[case] hash: &str,
+        ) {
+            let root = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+            let account_extended =
+                BlockExplorer::prepare_account_extended_key(root, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
+            let extended =
+                BlockExplorer::derive_extended_key(&account_extended.privkey, chain).unwrap();
+
+            assert_eq!(
+                BlockExplorer::derive_address(&extended.privkey, index)
+                    .unwrap()
+                    .hash,
+                hash
+            );
+        }
+
+        #[test]
+        fn test_prepare_extended_key_not_account() {
+            let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
+            let chain = "xprvA1Nd3YgbqxvrcjZsCA96KsyPrLMK4TbPQq983aiN4k9Sx3DM5aKBnY7ejaasiCcqEwSbLP7QFnDJr2qxcjKhr6fPzQUGrGTS42T5QfQK9tL";
+
+            assert!(matches!(
+                BlockExplorer::prepare_extended_key(root, Chain::External, Network::Bitcoin),
+                Err(WalletBipError::InvalidDepth {
+                    expected: 3,
+                    actual: 0
+                })
+            ));
+            assert!(
+                BlockExplorer::prepare_extended_key(chain, Chain::External, Network::Bitcoin)
+                    .is_err()
+            );
+        }
+    }
+
+    mod ethereum {
+        use super::*;
+        use crate::hd_wallet::{prepare_root, prepare_seed};
+        use rstest::rstest;
+
+        fn root() -> String {
+            let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+                .split(' ')
+                .collect::<Vec<_>>();
+
+            prepare_root(prepare_seed(&mnemonic, "").unwrap(), Network::Bitcoin).unwrap()
+        }
+
+        #[rstest]
+        #[case(
+            0,
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94",
+            "0x1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727"
+        )]
+        #[case(
+            1,
+            "0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0",
+            "0x9a983cb3d832fbde5ab49d692b7a8bf5b5d232479c99333d0fc8e1d21f1b55b6"
+        )]
+        fn test_prepare_addresses(#[case] index: u32, #[case] hash: &str, #[case] privkey: &str) {
+            let account_extended =
+                Ethereum::prepare_account_extended_key(&root(), &Coin::ETH, 0, Network::Bitcoin)
+                    .unwrap();
+            let extended =
+                Ethereum::derive_extended_key(&account_extended.privkey, Chain::External).unwrap();
+
+            let result = Ethereum::derive_address(&extended.privkey, index).unwrap();
+
+            assert_eq!(result.hash, hash);
+            assert_eq!(result.privkey, privkey);
+        }
+
+        #[rstest]
+        #[case("ETC", "m/44'/61'/0'")]
+        #[case("MATIC", "m/44'/966'/0'")]
+        fn test_prepare_account_key_registry(#[case] symbol: &str, #[case] path: &str) {
+            use crate::hd_wallet::{CoinRegistry, DerivationPath, derive};
+            use std::str::FromStr;
+
+            let registry = CoinRegistry::default();
+            let coin = registry.by_symbol(symbol).unwrap();
+
+            let account_extended =
+                Ethereum::prepare_account_extended_key(&root(), coin, 0, Network::Bitcoin).unwrap();
+
+            assert_eq!(
+                account_extended.privkey.to_string(),
+                derive(
+                    &root(),
+                    &DerivationPath::from_str(path).unwrap(),
+                    Network::Bitcoin
+                )
+                .unwrap()
+                .privkey
+                .to_string()
+            );
+        }
+
+        #[test]
+        fn test_prepare_account_key_bitcoin() {
+            assert!(matches!(
+                Ethereum::prepare_account_extended_key(&root(), &Coin::BTC, 0, Network::Bitcoin),
+                Err(WalletBipError::InvalidCoin(_))
+            ));
+        }
+
+        #[test]
+        fn test_prepare_descriptors_evm() {
+            let account_extended =
+                Ethereum::prepare_account_extended_key(&root(), &Coin::ETH, 0, Network::Bitcoin)
+                    .unwrap();
+
+            assert!(matches!(
+                Ethereum::prepare_descriptors(&account_extended),
+                Err(WalletBipError::Descriptor(_))
+            ));
+        }
     }
 }
@@ -0,0 +1,176 @@
+use crate::Result;
+use crate::hd_wallet::{
+    Chain, Client, Coin, ExtendedPrivKey, ExtendedPubPrivKey, Network, ScriptType, derive_account,
+    derive_chain,
+};
+
+pub trait Bip49: Client {
+    /// Derives the account keys `m/49'/coin'/account'` from a root key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not a root key, the client cannot encode
+    /// addresses of the coin or the derivation fails.
+    fn derive_account_extended_key(
+        root: &ExtendedPrivKey,
+        coin: &Coin,
+        account: u32,
+    ) -> Result<ExtendedPubPrivKey> {
+        derive_account(root, 49, coin, account, Self::SCRIPT_TYPE)
+    }
+
+    /// Derives the keys of a chain of an account key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not an account key or the derivation
+    /// fails.
+    fn derive_extended_key(account: &ExtendedPrivKey, chain: Chain) -> Result<ExtendedPubPrivKey> {
+        derive_chain(account, chain, Self::SCRIPT_TYPE)
+    }
+
+    /// Parses the root key, see [`Bip49::derive_account_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not a root key, the client cannot encode addresses of the
+    /// coin or the derivation fails.
+    fn prepare_account_extended_key(
+        root_key: &str,
+        coin: &Coin,
+        account: u32,
+        network: Network,
+    ) -> Result<ExtendedPubPrivKey> {
+        Self::derive_account_extended_key(
+            &ExtendedPrivKey::parse(root_key, network)?,
+            coin,
+            account,
+        )
+    }
+
+    /// Parses the account key, see [`Bip49::derive_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not an account key or the derivation fails.
+    fn prepare_extended_key(
+        account_key: &str,
+        chain: Chain,
+        network: Network,
+    ) -> Result<ExtendedPubPrivKey> {
+        Self::derive_extended_key(&ExtendedPrivKey::parse(account_key, network)?, chain)
+    }
+}
+
+pub struct NestedSegwit;
+
+impl Client for NestedSegwit {
+    const EXTENDED_KEY_DEPTH: u8 = 4;
+
+    const IS_HARDENED_ADDRESSES: bool = false;
+
+    const SCRIPT_TYPE: ScriptType = ScriptType::P2shP2wpkh;
+}
+
+impl Bip49 for NestedSegwit {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod nested_segwit {
+        use super::*;
+        use rstest::rstest;
+
+        const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+        const ACCOUNT: &str = "yprvAHwhK6RbpuS3dgCYHM5jc2ZvEKd7Bi61u9FVhYMpgMSuZS613T1xxQeKTffhrHY79hZ5PsskBjcc6C2V7DrnsMsNaGDaWev3GLRQRgV7hxF";
+
+        #[test]
+        fn test_prepare_account_key() {
+            let account_extended =
+                NestedSegwit::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
+
+            assert_eq!(account_extended.privkey.to_string(), ACCOUNT);
+            assert_eq!(
+                account_extended.pubkey.to_string(),
+                "ypub6Ww3ibxVfGzLrAH1PNcjyAWenMTbbAosGNB6VvmSEgytSER9azLDWCxoJwW7Ke7icmizBMXrzBx9979FfaHxHcrArf3zbeJJJUZPf663zsP"
+            );
+        }
+
+        #[rstest]
+        #[case(
+            Chain::External,
+            0,
+            "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf",
+            "039b3b694b8fc5b5e07fb069c783cac754f5d38c3e08bed1960e31fdb1dda35c24",
+            "KyvHbRLNXfXaHuZb3QRaeqA5wovkjg4RuUpFGCxdH5UWc1Foih9o"
+        )]
+        #[case(
+            Chain::External,
+            1,
+            "3LtMnn87fqUeHBUG414p9CWwnoV6E2pNKS",
+            "022a421fa4a65a87d1c3e4238155d85f7bd2c5bb87632f331b5722f110586aa198",
+            "KyaMvgopkPDQMQUx2w9a8AiEtA7A84hYzASJWGQiKZ8AJUEj77iV"
+        )]
+        #[case(
+            Chain::Internal,
+            0,
+            "34K56kSjgUCUSD8GTtuF7c9Zzwokbs6uZ7",
+            "02b4019c64bb1347bd729a6afa11348bd80be4ebc314df03f654f786bfe2b4a728",
+            "L3pspue7Ag5bBvfo4EHBASUQfhBAfh4WWU9XwGf6mBXy5GNY2UnS"
+        )]
+        fn test_prepare_addresses(
+            #[case] chain: Chain,
+            #[case] index: u32,
+            #[case] hash: &str,
+            #[case] pubkey: &str,
+            #[case] privkey: &str,
+        ) {
+            let extended =
+                NestedSegwit::prepare_extended_key(ACCOUNT, chain, Network::Bitcoin).unwrap();
+
+            let result = NestedSegwit::derive_address(&extended.privkey, index).unwrap();
+
+            assert_eq!(result.hash, hash);
+            assert_eq!(result.pubkey, pubkey);
+            assert_eq!(result.privkey, privkey);
+        }
+
+        #[rstest]
+        #[case(Network::Testnet, "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2")]
+        #[case(Network::Regtest, "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2")]
+        fn test_prepare_test_network_address(#[case] network: Network, #[case] hash: &str) {
+            let root = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd";
+
+            let account_extended =
+                NestedSegwit::prepare_account_extended_key(root, &Coin::BTC, 0, network).unwrap();
+
+            assert!(account_extended.privkey.to_string().starts_with("uprv"));
+
+            let extended =
+                NestedSegwit::derive_extended_key(&account_extended.privkey, Chain::External)
+                    .unwrap();
+            let result = NestedSegwit::derive_address(&extended.privkey, 0).unwrap();
+
+            assert_eq!(result.hash, hash);
+        }
+
+        #[test]
+        fn test_prepare_descriptors() {
+            let account_extended =
+                NestedSegwit::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
+
+            let descriptors = NestedSegwit::prepare_descriptors(&account_extended).unwrap();
+
+            assert_eq!(
+                descriptors.public().unwrap(),
+                "sh(wpkh([73c5da0a/49h/0h/0h]xpub6C6nQwHaWbSrzs5tZ1q7m5R9cPK9eYpNMFesiXsYrgc1P8bvLLAet9JfHjYXKjToD8cBRswJXXbbFpXgwsswVPAZzKMa1jUp2kVkGVUaJa7/<0;1>/*))#zmygnj3e"
+            );
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use crate::Result;
+use crate::hd_wallet::{
+    Chain, Client, Coin, ExtendedPrivKey, ExtendedPubPrivKey, Network, ScriptType, derive_account,
+    derive_chain,
+};
+
+pub trait Bip84: Client {
+    /// Derives the account keys `m/84'/coin'/account'` from a root key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not a root key, the client cannot encode
+    /// addresses of the coin or the derivation fails.
+    fn derive_account_extended_key(
+        root: &ExtendedPrivKey,
+        coin: &Coin,
+        account: u32,
+    ) -> Result<ExtendedPubPrivKey> {
+        derive_account(root, 84, coin, account, Self::SCRIPT_TYPE)
+    }
+
+    /// Derives the keys of a chain of an account key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not an account key or the derivation
+    /// fails.
+    fn derive_extended_key(account: &ExtendedPrivKey, chain: Chain) -> Result<ExtendedPubPrivKey> {
+        derive_chain(account, chain, Self::SCRIPT_TYPE)
+    }
+
+    /// Parses the root key, see [`Bip84::derive_account_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not a root key, the client cannot encode addresses of the
+    /// coin or the derivation fails.
+    fn prepare_account_extended_key(
+        root_key: &str,
+        coin: &Coin,
+        account: u32,
+        network: Network,
+    ) -> Result<ExtendedPubPrivKey> {
+        Self::derive_account_extended_key(
+            &ExtendedPrivKey::parse(root_key, network)?,
+            coin,
+            account,
+        )
+    }
+
+    /// Parses the account key, see [`Bip84::derive_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not an account key or the derivation fails.
+    fn prepare_extended_key(
+        account_key: &str,
+        chain: Chain,
+        network: Network,
+    ) -> Result<ExtendedPubPrivKey> {
+        Self::derive_extended_key(&ExtendedPrivKey::parse(account_key, network)?, chain)
+    }
+}
+
+pub struct NativeSegwit;
+
+impl Client for NativeSegwit {
+    const EXTENDED_KEY_DEPTH: u8 = 4;
+
+    const IS_HARDENED_ADDRESSES: bool = false;
+
+    const SCRIPT_TYPE: ScriptType = ScriptType::P2wpkh;
+}
+
+impl Bip84 for NativeSegwit {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod native_segwit {
+        use super::*;
+        use rstest::rstest;
+
+        const ROOT: &str = "zprvAWgYBBk7JR8Gjrh4UJQ2uJdG1r3WNRRfURiABBE3RvMXYSrRJL62XuezvGdPvG6GFBZduosCc1YP5wixPox7zhZLfiUm8aunE96BBa4Kei5";
+
+        const ACCOUNT: &str = "zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE";
+
+        #[test]
+        fn test_prepare_account_key() {
+            let account_extended =
+                NativeSegwit::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
+
+            assert_eq!(account_extended.privkey.to_string(), ACCOUNT);
+            assert_eq!(
+                account_extended.pubkey.to_string(),
+                "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs"
+            );
+        }
+
+        #[rstest]
+        #[case(
+            Chain::External,
+            0,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            "0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c",
+            "KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d"
+        )]
+        #[case(
+            Chain::External,
+            1,
+            "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g",
+            "03e775fd51f0dfb8cd865d9ff1cca2a158cf651fe997fdc9fee9c1d3b5e995ea77",
+            "Kxpf5b8p3qX56DKEe5NqWbNUP9MnqoRFzZwHRtsFqhzuvUJsYZCy"
+        )]
+        #[case(
+            Chain::Internal,
+            0,
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el",
+            "03025324888e429ab8e3dbaf1f7802648b9cd01e9b418485c5fa4c1b9b5700e1a6",
+            "KxuoxufJL5csa1Wieb2kp29VNdn92Us8CoaUG3aGtPtcF3AzeXvF"
+        )]
+        fn test_prepare_addresses(
+            #[case] chain: Chain,
+            #[case] index: u32,
+            #[case] hash: &str,
+            #[case] pubkey: &str,
+            #[case] privkey: &str,
+        ) {
+            let extended =
+                NativeSegwit::prepare_extended_key(ACCOUNT, chain, Network::Bitcoin).unwrap();
+
+            let result = NativeSegwit::derive_address(&extended.privkey, index).unwrap();
+
+            assert_eq!(result.hash, hash);
+            assert_eq!(result.pubkey, pubkey);
+            assert_eq!(result.privkey, privkey);
+        }
+
+        #[rstest]
+        #[case(Chain::External, 0, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")]
+        #[case(Chain::External, 1, "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g")]
+        #[case(Chain::Internal, 0, "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el")]
+        fn test_prepare_watch_only_addresses(
+            #[case] chain: Chain,
+            #[case] index: u32,
+            #[case] hash: &str,
+        ) {
+            let extended =
+                NativeSegwit::prepare_extended_key(ACCOUNT, chain, Network::Bitcoin).unwrap();
+
+            assert!(extended.pubkey.to_string().starts_with("zpub"));
+
+            let result = NativeSegwit::derive_watch_only_address(&extended.pubkey, index).unwrap();
+
+            assert_eq!(result.hash, hash);
+            assert_eq!(
+                result.pubkey,
+                NativeSegwit::derive_address(&extended.privkey, index)
+                    .unwrap()
+                    .pubkey
+            );
+        }
+
+        #[rstest]
+        #[case(Network::Testnet, "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl")]
+        #[case(Network::Signet, "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl")]
+        #[case(Network::Regtest, "bcrt1q6rz28mcfaxtmd6v789l9rrlrusdprr9pz3cppk")]
+        fn test_prepare_test_network_address(#[case] network: Network, #[case] hash: &str) {
+            let root = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd";
+
+            let account_extended =
+                NativeSegwit::prepare_account_extended_key(root, &Coin::BTC, 0, network).unwrap();
+
+            assert!(account_extended.privkey.to_string().starts_with("vprv"));
+
+            let extended =
+                NativeSegwit::derive_extended_key(&account_extended.privkey, Chain::External)
+                    .unwrap();
+            let result = NativeSegwit::derive_address(&extended.privkey, 0).unwrap();
+
+            assert_eq!(result.hash, hash);
+        }
+
+        #[test]
+        fn test_prepare_descriptors() {
+            let account_extended =
+                NativeSegwit::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
+
+            let descriptors = NativeSegwit::prepare_descriptors(&account_extended).unwrap();
+
+            assert_eq!(
+                descriptors.public().unwrap(),
+                "wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/<0;1>/*)#qf45pmyh"
+            );
+            assert_eq!(
+                descriptors.private,
+                "wpkh([73c5da0a/84h/0h/0h]xprv9ybY78BftS5UGANki6oSifuQEjkpyAC8ZmBvBNTshQnCBcxnefjHS7buPMkkqhcRzmoGZ5bokx7GuyDAiktd5HemohAU4wV1ZPMDRmLpBMm/<0;1>/*)#aeunql2k"
+            );
+        }
+
+        #[test]
+        fn test_prepare_testnet_descriptors() {
+            let root = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd";
+
+            let account_extended =
+                NativeSegwit::prepare_account_extended_key(root, &Coin::BTC, 0, Network::Testnet)
+                    .unwrap();
+
+            let descriptors = NativeSegwit::prepare_descriptors(&account_extended).unwrap();
+
+            assert_eq!(
+                descriptors.public().unwrap(),
+                "wpkh([73c5da0a/84h/1h/0h]tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*)#lxek0ef2"
+            );
+        }
+
+        #[test]
+        fn test_prepare_chain_descriptors() {
+            let extended =
+                NativeSegwit::prepare_extended_key(ACCOUNT, Chain::External, Network::Bitcoin)
+                    .unwrap();
+
+            let descriptors = NativeSegwit::prepare_descriptors(&extended).unwrap();
+
+            assert!(descriptors.public().unwrap().starts_with("wpkh(xpub"));
+            assert!(descriptors.public().unwrap().contains("/*)#"));
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::Result;
+use crate::hd_wallet::{
+    Chain, Client, Coin, ExtendedPrivKey, ExtendedPubPrivKey, Network, ScriptType, derive_account,
+    derive_chain,
+};
+
+pub trait Bip86: Client {
+    /// Derives the account keys `m/86'/coin'/account'` from a root key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not a root key, the client cannot encode
+    /// addresses of the coin or the derivation fails.
+    fn derive_account_extended_key(
+        root: &ExtendedPrivKey,
+        coin: &Coin,
+        account: u32,
+    ) -> Result<ExtendedPubPrivKey> {
+        derive_account(root, 86, coin, account, Self::SCRIPT_TYPE)
+    }
+
+    /// Derives the keys of a chain of an account key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not an account key or the derivation
+    /// fails.
+    fn derive_extended_key(account: &ExtendedPrivKey, chain: Chain) -> Result<ExtendedPubPrivKey> {
+        derive_chain(account, chain, Self::SCRIPT_TYPE)
+    }
+
+    /// Parses the root key, see [`Bip86::derive_account_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not a root key, the client cannot encode addresses of the
+    /// coin or the derivation fails.
+    fn prepare_account_extended_key(
+        root_key: &str,
+        coin: &Coin,
+        account: u32,
+        network: Network,
+    ) -> Result<ExtendedPubPrivKey> {
+        Self::derive_account_extended_key(
+            &ExtendedPrivKey::parse(root_key, network)?,
+            coin,
+            account,
+        )
+    }
+
+    /// Parses the account key, see [`Bip86::derive_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not an account key or the derivation fails.
+    fn prepare_extended_key(
+        account_key: &str,
+        chain: Chain,
+        network: Network,
+    ) -> Result<ExtendedPubPrivKey> {
+        Self::derive_extended_key(&ExtendedPrivKey::parse(account_key, network)?, chain)
+    }
+}
+
+pub struct Taproot;
+
+impl Client for Taproot {
+    const EXTENDED_KEY_DEPTH: u8 = 4;
+
+    const IS_HARDENED_ADDRESSES: bool = false;
+
+    const SCRIPT_TYPE: ScriptType = ScriptType::P2tr;
+}
+
+impl Bip86 for Taproot {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod taproot {
+        use super::*;
+        use rstest::rstest;
+
+        const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+        const ACCOUNT: &str = "xprv9xgqHN7yz9MwCkxsBPN5qetuNdQSUttZNKw1dcYTV4mkaAFiBVGQziHs3NRSWMkCzvgjEe3n9xV8oYywvM8at9yRqyaZVz6TYYhX98VjsUk";
+
+        #[test]
+        fn test_prepare_account_key() {
+            let account_extended =
+                Taproot::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
+
+            assert_eq!(account_extended.privkey.to_string(), ACCOUNT);
+            assert_eq!(
+                account_extended.pubkey.to_string(),
+                "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ"
+            );
+        }
+
+        #[rstest]
+        #[case(
+            Chain::External,
+            0,
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            "03cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115",
+            "KyRv5iFPHG7iB5E4CqvMzH3WFJVhbfYK4VY7XAedd9Ys69mEsPLQ"
+        )]
+        #[case(
+            Chain::External,
+            1,
+            "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh",
+            "0283dfe85a3151d2517290da461fe2815591ef69f2b18a2ce63f01697a8b313145",
+            "L1jhNnZZAAAppoSYQuaAQEj935VpmishMomuWXgJ3Qy5HNqkhhus"
+        )]
+        #[case(
+            Chain::Internal,
+            0,
+            "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7",
+            "02399f1b2f4393f29a18c937859c5dd8a77350103157eb880f02e8c08214277cef",
+            "KzsCLFtWKpeNKMHFyHKT8vGRuGQxEY8CQjgLcEj14C8xK2PyEFeN"
+        )]
+        fn test_prepare_addresses(
+            #[case] chain: Chain,
+            #[case] index: u32,
+            #[case] hash: &str,
+            #[case] pubkey: &str,
+            #[case] privkey: &str,
+        ) {
+            let extended = Taproot::prepare_extended_key(ACCOUNT, chain, Network::Bitcoin).unwrap();
+
+            let result = Taproot::derive_address(&extended.privkey, index).unwrap();
+
+            assert_eq!(result.hash, hash);
+            assert_eq!(result.pubkey, pubkey);
+            assert_eq!(result.privkey, privkey);
+        }
+
+        #[rstest]
+        #[case(
+            Network::Testnet,
+            "tb1p8wpt9v4frpf3tkn0srd97pksgsxc5hs52lafxwru9kgeephvs7rqlqt9zj"
+        )]
+        #[case(
+            Network::Regtest,
+            "bcrt1p8wpt9v4frpf3tkn0srd97pksgsxc5hs52lafxwru9kgeephvs7rqjeprhg"
+        )]
+        fn test_prepare_test_network_address(#[case] network: Network, #[case] hash: &str) {
+            let root = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd";
+
+            let account_extended =
+                Taproot::prepare_account_extended_key(root, &Coin::BTC, 0, network).unwrap();
+
+            assert!(account_extended.privkey.to_string().starts_with("tprv"));
+
+            let extended =
+                Taproot::derive_extended_key(&account_extended.privkey, Chain::External).unwrap();
+            let result = Taproot::derive_address(&extended.privkey, 0).unwrap();
+
+            assert_eq!(result.hash, hash);
+        }
+
+        #[test]
+        fn test_prepare_descriptors() {
+            let account_extended =
+                Taproot::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin)
+                    .unwrap();
+
+            let descriptors = Taproot::prepare_descriptors(&account_extended).unwrap();
+
+            assert_eq!(
+                descriptors.public().unwrap(),
+                "tr([73c5da0a/86h/0h/0h]xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/<0;1>/*)#xf07c0qd"
+            );
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use crate::Result;
+use crate::hd_wallet::{
+    Address, AddressParams, ExtendedPrivKey, ExtendedPubKey, ScriptType, WatchOnlyAddress,
+};
+use bip32::{ChildNumber, XPrv, XPub};
+use core::ops::Range;
+
+/// Addresses of consecutive indices of a parent key, derived lazily.
+///
+/// Every index is an independent child derivation of the parent key, the
+/// rayon iterator spreads them over the thread pool.
+#[derive(Clone)]
+pub struct Addresses {
+    key: XPrv,
+    indexes: Range<u32>,
+    is_hardened: bool,
+    script_type: ScriptType,
+    params: AddressParams,
+}
+
+/// Watch-only addresses of consecutive indices of a parent public key, derived
+/// lazily.
+#[derive(Clone)]
+pub struct WatchOnlyAddresses {
+    key: XPub,
+    indexes: Range<u32>,
+    script_type: ScriptType,
+    params: AddressParams,
+}
+
+impl Addresses {
+    pub(crate) fn new(
+        extended: &ExtendedPrivKey,
+        indexes: Range<u32>,
+        is_hardened: bool,
+        script_type: ScriptType,
+    ) -> Self {
+        Self {
+            key: extended.as_xprv().clone(),
+            indexes,
+            is_hardened,
+            script_type,
+            params: extended.address_params(),
+        }
+    }
+
+    fn derive(&self, index: u32) -> Result<Address> {
+        let child = self
+            .key
+            .derive_child(ChildNumber::new(index, self.is_hardened)?)?;
+        let privkey = child.private_key();
+
+        Address::from_keys(
+            privkey.verifying_key(),
+            privkey,
+            self.script_type,
+            self.params,
+        )
+    }
+
+    /// Derives the remaining addresses on the rayon thread pool, in index
+    /// order.
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn into_par_iter(
+        self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = Result<Address>> {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+        self.indexes
+            .clone()
+            .into_par_iter()
+            .map(move |index| self.derive(index))
+    }
+}
+
+impl WatchOnlyAddresses {
+    pub(crate) fn new(
+        extended: &ExtendedPubKey,
+        indexes: Range<u32>,
+        script_type: ScriptType,
+    ) -> Self {
+        Self {
+            key: extended.as_xpub().clone(),
+            indexes,
+            script_type,
+            params: extended.address_params(),
+        }
+    }
+
+    fn derive(&self, index: u32) -> Result<WatchOnlyAddress> {
+        let child = self.key.derive_child(ChildNumber::new(index, false)?)?;
+
+        WatchOnlyAddress::from_key(child.public_key(), self.script_type, self.params)
+    }
+
+    /// Derives the remaining addresses on the rayon thread pool, in index
+    /// order.
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn into_par_iter(
+        self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = Result<WatchOnlyAddress>> {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+        self.indexes
+            .clone()
+            .into_par_iter()
+            .map(move |index| self.derive(index))
+    }
+}
+
+impl Iterator for Addresses {
+    type Item = Result<Address>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indexes.next().map(|index| self.derive(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indexes.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Addresses {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.indexes.next_back().map(|index| self.derive(index))
+    }
+}
+
+impl ExactSizeIterator for Addresses {}
+
+impl Iterator for WatchOnlyAddresses {
+    type Item = Result<WatchOnlyAddress>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indexes.next().map(|index| self.derive(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indexes.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for WatchOnlyAddresses {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.indexes.next_back().map(|index| self.derive(index))
+    }
+}
+
+impl ExactSizeIterator for WatchOnlyAddresses {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::b32::BitcoinCore;
+    use crate::hd_wallet::b84::NativeSegwit;
+    use crate::hd_wallet::{Client, Network};
+    use rstest::rstest;
+
+    const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    fn chain_key(path: &str) -> ExtendedPrivKey {
+        crate::hd_wallet::derive(ROOT, &path.parse().unwrap(), Network::Bitcoin)
+            .unwrap()
+            .privkey
+    }
+
+    #[test]
+    fn test_derive_addresses() {
+        let extended = chain_key("m/84'/0'/0'/0");
+
+        let hashes = NativeSegwit::derive_addresses(&extended, 0..2)
+            .unwrap()
+            .map(|address| address.unwrap().hash)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            hashes,
+            [
+                "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+                "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g",
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(0..5)]
+    #[case(1000..1003)]
+    fn test_derive_addresses_matches_single(#[case] indexes: Range<u32>) {
+        let extended = chain_key("m/84'/0'/0'/1");
+
+        for (index, address) in indexes
+            .clone()
+            .zip(NativeSegwit::derive_addresses(&extended, indexes.clone()).unwrap())
+        {
+            let address = address.unwrap();
+            let single = NativeSegwit::derive_address(&extended, index).unwrap();
+
+            assert_eq!(address.hash, single.hash);
+            assert_eq!(address.privkey, single.privkey);
+        }
+
+        for (index, address) in indexes.clone().zip(
+            NativeSegwit::derive_watch_only_addresses(&extended.public_key(), indexes).unwrap(),
+        ) {
+            let single =
+                NativeSegwit::derive_watch_only_address(&extended.public_key(), index).unwrap();
+
+            assert_eq!(address.unwrap().hash, single.hash);
+        }
+    }
+
+    #[test]
+    fn test_derive_hardened_addresses() {
+        let extended = chain_key("m/0'/0'");
+
+        for (index, address) in (0..3).zip(BitcoinCore::derive_addresses(&extended, 0..3).unwrap())
+        {
+            assert_eq!(
+                address.unwrap().privkey,
+                BitcoinCore::derive_address(&extended, index)
+                    .unwrap()
+                    .privkey
+            );
+        }
+    }
+
+    #[test]
+    fn test_derive_addresses_out_of_range() {
+        let extended = chain_key("m/84'/0'/0'/0");
+
+        let mut addresses =
+            NativeSegwit::derive_addresses(&extended, 0x7fff_ffff..0x8000_0001).unwrap();
+
+        assert_eq!(addresses.len(), 2);
+        assert!(addresses.next().unwrap().is_ok());
+        assert!(addresses.next().unwrap().is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_iter() {
+        use rayon::iter::ParallelIterator;
+
+        let extended = chain_key("m/84'/0'/0'/0");
+
+        let sequential = NativeSegwit::derive_addresses(&extended, 0..50)
+            .unwrap()
+            .map(|address| address.unwrap().hash)
+            .collect::<Vec<_>>();
+        let parallel = NativeSegwit::derive_addresses(&extended, 0..50)
+            .unwrap()
+            .into_par_iter()
+            .map(|address| address.unwrap().hash)
+            .collect::<Vec<_>>();
+
+        assert_eq!(sequential, parallel);
+    }
+}
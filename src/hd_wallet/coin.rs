@@ -0,0 +1,408 @@
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::{AddressParams, Network, ScriptType};
+use std::borrow::Cow;
+
+/// A coin registered in SLIP-44, the coin type is the second level of
+/// BIP44-like derivation paths.
+///
+/// The crate encodes the addresses of EVM chains and of coins built on the
+/// Bitcoin address formats, with the [`AddressParams`] of the coin. The
+/// default address format tells which of them a coin uses, coins without one
+/// can be derived with [`derive`](crate::hd_wallet::derive) but the clients
+/// refuse to build their addresses.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Coin {
+    index: u32,
+    symbol: Cow<'static, str>,
+    name: Cow<'static, str>,
+    script_type: Option<ScriptType>,
+    params: AddressParams,
+}
+
+impl Coin {
+    pub const BTC: Coin = Coin::bitcoin_like(0, "BTC", "Bitcoin", AddressParams::BITCOIN);
+
+    pub const ETH: Coin = Coin::slip44(60, "ETH", "Ethereum", Some(ScriptType::Evm));
+
+    /// Builds a custom coin, `script_type` is the default address format and
+    /// `None` if the crate cannot produce addresses of the coin. A Bitcoin
+    /// script type uses the Bitcoin address parameters, see
+    /// [`Coin::with_address_params`].
+    pub fn new(
+        coin_type: u32,
+        symbol: impl Into<Cow<'static, str>>,
+        name: impl Into<Cow<'static, str>>,
+        script_type: Option<ScriptType>,
+    ) -> Self {
+        Self {
+            index: coin_type,
+            symbol: symbol.into(),
+            name: name.into(),
+            script_type,
+            params: AddressParams::BITCOIN,
+        }
+    }
+
+    /// Sets the mainnet address parameters of a coin with a Bitcoin address
+    /// format.
+    #[must_use]
+    pub fn with_address_params(self, params: AddressParams) -> Self {
+        Self { params, ..self }
+    }
+
+    const fn slip44(
+        coin_type: u32,
+        symbol: &'static str,
+        name: &'static str,
+        script_type: Option<ScriptType>,
+    ) -> Self {
+        Self {
+            index: coin_type,
+            symbol: Cow::Borrowed(symbol),
+            name: Cow::Borrowed(name),
+            script_type,
+            params: AddressParams::BITCOIN,
+        }
+    }
+
+    /// A coin with legacy addresses by default and its own address
+    /// parameters.
+    const fn bitcoin_like(
+        coin_type: u32,
+        symbol: &'static str,
+        name: &'static str,
+        params: AddressParams,
+    ) -> Self {
+        Self {
+            index: coin_type,
+            symbol: Cow::Borrowed(symbol),
+            name: Cow::Borrowed(name),
+            script_type: Some(ScriptType::P2pkh),
+            params,
+        }
+    }
+
+    #[must_use]
+    pub fn coin_type(&self) -> u32 {
+        self.index
+    }
+
+    #[must_use]
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn script_type(&self) -> Option<ScriptType> {
+        self.script_type
+    }
+
+    /// The address parameters on a network, test networks share coin type 1
+    /// and use the Bitcoin test parameters whatever the coin is.
+    #[must_use]
+    pub fn address_params(&self, network: Network) -> AddressParams {
+        if network.is_mainnet() {
+            self.params
+        } else {
+            network.address_params()
+        }
+    }
+
+    /// Whether the crate can encode addresses of the coin with the script
+    /// type, EVM accounts need an EVM coin, Bitcoin scripts a coin with a
+    /// Bitcoin address format and segwit scripts a coin with segwit
+    /// addresses.
+    #[must_use]
+    pub fn supports(&self, script_type: ScriptType) -> bool {
+        match (self.script_type, script_type) {
+            (Some(ScriptType::Evm), script_type) => script_type == ScriptType::Evm,
+            (None, _) | (Some(_), ScriptType::Evm) => false,
+            (Some(_), ScriptType::P2pkh) => true,
+            (Some(_), ScriptType::P2shP2wpkh | ScriptType::P2wpkh | ScriptType::P2tr) => {
+                self.params.is_segwit()
+            }
+        }
+    }
+
+    pub(crate) fn check_script_type(&self, script_type: ScriptType) -> Result<()> {
+        if !self.supports(script_type) {
+            return Err(WalletBipError::InvalidCoin(format!(
+                "{} addresses cannot be encoded as {script_type:?}",
+                self.symbol
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A curated selection of widely used SLIP-44 coins, known by default. It is
+/// not the SLIP-44 table, other coins of the table are added with
+/// [`CoinRegistry::register`].
+const KNOWN_COINS: &[Coin] = &[
+    Coin::BTC,
+    Coin::bitcoin_like(
+        2,
+        "LTC",
+        "Litecoin",
+        AddressParams::new(0x30, 0x32, 0xb0, Some("ltc")),
+    ),
+    Coin::bitcoin_like(
+        3,
+        "DOGE",
+        "Dogecoin",
+        AddressParams::new(0x1e, 0x16, 0x9e, None),
+    ),
+    Coin::bitcoin_like(
+        4,
+        "RDD",
+        "Reddcoin",
+        AddressParams::new(0x3d, 0x05, 0xbd, None),
+    ),
+    Coin::bitcoin_like(
+        5,
+        "DASH",
+        "Dash",
+        AddressParams::new(0x4c, 0x10, 0xcc, None),
+    ),
+    Coin::bitcoin_like(
+        6,
+        "PPC",
+        "Peercoin",
+        AddressParams::new(0x37, 0x75, 0xb7, None),
+    ),
+    Coin::bitcoin_like(
+        7,
+        "NMC",
+        "Namecoin",
+        AddressParams::new(0x34, 0x0d, 0xb4, Some("nc")),
+    ),
+    Coin::bitcoin_like(
+        14,
+        "VIA",
+        "Viacoin",
+        AddressParams::new(0x47, 0x21, 0xc7, Some("via")),
+    ),
+    Coin::bitcoin_like(
+        20,
+        "DGB",
+        "DigiByte",
+        AddressParams::new(0x1e, 0x3f, 0x80, Some("dgb")),
+    ),
+    Coin::bitcoin_like(
+        22,
+        "MONA",
+        "Monacoin",
+        AddressParams::new(0x32, 0x37, 0xb0, Some("mona")),
+    ),
+    Coin::bitcoin_like(
+        28,
+        "VTC",
+        "Vertcoin",
+        AddressParams::new(0x47, 0x05, 0x80, Some("vtc")),
+    ),
+    Coin::slip44(42, "DCR", "Decred", None),
+    Coin::slip44(43, "XEM", "NEM", None),
+    Coin::ETH,
+    Coin::slip44(61, "ETC", "Ethereum Classic", Some(ScriptType::Evm)),
+    Coin::slip44(74, "ICX", "ICON", None),
+    Coin::bitcoin_like(
+        77,
+        "XVG",
+        "Verge",
+        AddressParams::new(0x1e, 0x21, 0x9e, None),
+    ),
+    Coin::slip44(118, "ATOM", "Atom", None),
+    Coin::slip44(128, "XMR", "Monero", None),
+    Coin::slip44(133, "ZEC", "Zcash", None),
+    Coin::slip44(134, "LSK", "Lisk", None),
+    Coin::slip44(137, "RBTC", "RSK", None),
+    Coin::slip44(144, "XRP", "XRP", None),
+    Coin::slip44(145, "BCH", "Bitcoin Cash", None),
+    Coin::slip44(148, "XLM", "Stellar Lumens", None),
+    Coin::bitcoin_like(
+        156,
+        "BTG",
+        "Bitcoin Gold",
+        AddressParams::new(0x26, 0x17, 0x80, Some("btg")),
+    ),
+    Coin::slip44(194, "EOS", "EOS", None),
+    Coin::slip44(195, "TRX", "Tron", None),
+    Coin::slip44(236, "BSV", "Bitcoin SV", None),
+    Coin::slip44(283, "ALGO", "Algorand", None),
+    Coin::slip44(354, "DOT", "Polkadot", None),
+    Coin::slip44(397, "NEAR", "NEAR Protocol", None),
+    Coin::slip44(434, "KSM", "Kusama", None),
+    Coin::slip44(461, "FIL", "Filecoin", None),
+    Coin::slip44(501, "SOL", "Solana", None),
+    Coin::slip44(637, "APT", "Aptos", None),
+    Coin::slip44(700, "XDAI", "xDai", Some(ScriptType::Evm)),
+    Coin::slip44(714, "BNB", "Binance", None),
+    Coin::slip44(784, "SUI", "Sui", None),
+    Coin::slip44(818, "VET", "VeChain Token", Some(ScriptType::Evm)),
+    Coin::slip44(966, "MATIC", "Matic", Some(ScriptType::Evm)),
+    Coin::slip44(1007, "FTM", "Fantom", Some(ScriptType::Evm)),
+    Coin::slip44(1815, "ADA", "Cardano", None),
+    Coin::slip44(9000, "AVAX", "Avalanche", None),
+    Coin::slip44(52752, "CELO", "Celo", Some(ScriptType::Evm)),
+];
+
+/// Coins available for derivation, looked up by symbol or coin type.
+///
+/// The default registry is a curated subset of about 45 SLIP-44 coins, not
+/// the whole table. Coins with a Bitcoin address format carry their own
+/// address parameters, Bitcoin Cash, Zcash and the other coins with their own
+/// address encodings have no address format.
+#[derive(Clone, Debug)]
+pub struct CoinRegistry {
+    coins: Vec<Coin>,
+}
+
+impl Default for CoinRegistry {
+    fn default() -> Self {
+        Self {
+            coins: KNOWN_COINS.to_vec(),
+        }
+    }
+}
+
+impl CoinRegistry {
+    /// Registers a custom coin.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a coin with the same coin type or symbol is already
+    /// registered or the coin type is hardened.
+    pub fn register(&mut self, coin: Coin) -> Result<()> {
+        if coin.index >= bip32::ChildNumber::HARDENED_FLAG {
+            return Err(WalletBipError::InvalidCoin(format!(
+                "coin type {} is out of range",
+                coin.index
+            )));
+        }
+
+        if let Some(known) = self.coins.iter().find(|known| {
+            known.index == coin.index || known.symbol.eq_ignore_ascii_case(&coin.symbol)
+        }) {
+            return Err(WalletBipError::InvalidCoin(format!(
+                "{} conflicts with registered {} ({})",
+                coin.symbol, known.symbol, known.index
+            )));
+        }
+
+        self.coins.push(coin);
+
+        Ok(())
+    }
+
+    /// Finds a coin by its ticker symbol, ignoring case.
+    #[must_use]
+    pub fn by_symbol(&self, symbol: &str) -> Option<&Coin> {
+        self.coins
+            .iter()
+            .find(|coin| coin.symbol.eq_ignore_ascii_case(symbol))
+    }
+
+    #[must_use]
+    pub fn by_coin_type(&self, coin_type: u32) -> Option<&Coin> {
+        self.coins.iter().find(|coin| coin.index == coin_type)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Coin> {
+        self.coins.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn known(symbol: &str) -> Coin {
+        CoinRegistry::default().by_symbol(symbol).unwrap().clone()
+    }
+
+    #[rstest]
+    #[case("BTC", 0)]
+    #[case("ltc", 2)]
+    #[case("Eth", 60)]
+    #[case("SOL", 501)]
+    fn test_by_symbol(#[case] symbol: &str, #[case] coin_type: u32) {
+        let registry = CoinRegistry::default();
+
+        assert_eq!(
+            registry.by_symbol(symbol).map(Coin::coin_type),
+            Some(coin_type)
+        );
+    }
+
+    #[test]
+    fn test_by_coin_type() {
+        let registry = CoinRegistry::default();
+
+        assert_eq!(registry.by_coin_type(60), Some(&Coin::ETH));
+        assert_eq!(registry.by_coin_type(1), None);
+    }
+
+    #[test]
+    fn test_known_coins_unique() {
+        let registry = CoinRegistry::default();
+
+        for (i, coin) in registry.iter().enumerate() {
+            assert!(
+                registry
+                    .iter()
+                    .skip(i + 1)
+                    .all(|other| { other.index != coin.index && other.symbol != coin.symbol })
+            );
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = CoinRegistry::default();
+
+        registry
+            .register(Coin::new(
+                5_000_000,
+                "TST",
+                "Test coin",
+                Some(ScriptType::P2wpkh),
+            ))
+            .unwrap();
+
+        assert_eq!(registry.by_symbol("tst").map(Coin::name), Some("Test coin"));
+    }
+
+    #[rstest]
+    #[case(Coin::BTC, ScriptType::P2wpkh, true)]
+    #[case(Coin::BTC, ScriptType::Evm, false)]
+    #[case(Coin::ETH, ScriptType::Evm, true)]
+    #[case(Coin::ETH, ScriptType::P2pkh, false)]
+    #[case(Coin::slip44(1815, "ADA", "Cardano", None), ScriptType::P2pkh, false)]
+    #[case(known("LTC"), ScriptType::P2wpkh, true)]
+    #[case(known("DOGE"), ScriptType::P2pkh, true)]
+    #[case(known("DOGE"), ScriptType::P2shP2wpkh, false)]
+    fn test_supports(#[case] coin: Coin, #[case] script_type: ScriptType, #[case] expected: bool) {
+        assert_eq!(coin.supports(script_type), expected);
+    }
+
+    #[rstest]
+    #[case(Coin::new(0, "XBT", "Bitcoin again", None))]
+    #[case(Coin::new(5_000_000, "btc", "Not bitcoin", None))]
+    #[case(Coin::new(0x8000_0000, "BIG", "Hardened", None))]
+    fn test_register_invalid(#[case] coin: Coin) {
+        let mut registry = CoinRegistry::default();
+
+        assert!(matches!(
+            registry.register(coin),
+            Err(WalletBipError::InvalidCoin(_))
+        ));
+    }
+}
@@ -0,0 +1,302 @@
+mod parse;
+
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::{DerivationPath, ExtendedPubPrivKey, ScriptType};
+use bip32::KeyFingerprint;
+use core::fmt;
+
+pub use parse::Descriptor;
+
+/// Characters allowed in a descriptor, their position feeds the checksum
+/// (BIP380).
+const INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+
+/// Characters of the checksum, the bech32 alphabet.
+const CHECKSUM_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// The number of characters of the checksum.
+const CHECKSUM_LENGTH: usize = 8;
+
+/// The fingerprint of the master key and the path a key was derived at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyOrigin {
+    pub(crate) fingerprint: KeyFingerprint,
+    pub(crate) path: DerivationPath,
+}
+
+impl KeyOrigin {
+    #[must_use]
+    pub fn new(fingerprint: KeyFingerprint, path: DerivationPath) -> Self {
+        Self { fingerprint, path }
+    }
+
+    /// The fingerprint of the master key.
+    #[must_use]
+    pub fn fingerprint(&self) -> KeyFingerprint {
+        self.fingerprint
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &DerivationPath {
+        &self.path
+    }
+}
+
+impl fmt::Display for KeyOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+
+        for b in self.fingerprint {
+            write!(f, "{b:02x}")?;
+        }
+
+        for child in self.path.iter() {
+            write!(f, "/{}", child.index())?;
+
+            if child.is_hardened() {
+                write!(f, "h")?;
+            }
+        }
+
+        write!(f, "]")
+    }
+}
+
+/// Output descriptors of an account, with their checksum.
+///
+/// The private descriptor is left out of the `Debug` output.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Descriptors {
+    pub(crate) public: Option<String>,
+    pub(crate) private: String,
+}
+
+impl fmt::Debug for Descriptors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Descriptors")
+            .field("public", &self.public)
+            .field("private", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Descriptors {
+    /// The descriptor with the extended public key, for watch-only wallets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the addresses use hardened indices, a public key
+    /// cannot derive them and only the private descriptor is usable.
+    pub fn public(&self) -> Result<&str> {
+        self.public.as_deref().ok_or_else(|| {
+            WalletBipError::HardenedFromPublic(
+                "addresses use hardened indices, only the private descriptor can derive them"
+                    .to_string(),
+            )
+        })
+    }
+
+    /// The descriptor with the extended private key.
+    #[must_use]
+    pub fn private(&self) -> &str {
+        &self.private
+    }
+}
+
+/// Builds the public and private descriptors of an extended key.
+///
+/// An account key, one level above the address keys, gets the `<0;1>/*`
+/// multipath suffix covering the receive and change chains, a chain key gets
+/// `/*`. Hardened addresses get a `*h` wildcard and no public descriptor.
+///
+/// # Errors
+///
+/// Returns an error if the script type has no descriptor or the keys have an
+/// unexpected depth.
+pub(crate) fn descriptors(
+    extended: &ExtendedPubPrivKey,
+    script_type: ScriptType,
+    address_depth: u8,
+    is_hardened_addresses: bool,
+) -> Result<Descriptors> {
+    let depth = extended.pubkey.depth();
+
+    let wildcard = if is_hardened_addresses { "*h" } else { "*" };
+
+    let suffix = match address_depth.checked_sub(depth) {
+        Some(0) => format!("/{wildcard}"),
+        Some(1) if !is_hardened_addresses => format!("/<0;1>/{wildcard}"),
+        _ => {
+            return Err(WalletBipError::Descriptor(format!(
+                "key depth {depth} does not fit addresses at depth {address_depth}"
+            )));
+        }
+    };
+
+    let origin = extended
+        .origin
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+
+    // descriptors only know the xpub and tpub versions
+    let pubkey = extended.pubkey.clone().with_script_type(ScriptType::P2pkh);
+    let privkey = extended.privkey.clone().with_script_type(ScriptType::P2pkh);
+
+    let public = if is_hardened_addresses {
+        None
+    } else {
+        Some(with_checksum(&wrap(
+            script_type,
+            &format!("{origin}{pubkey}{suffix}"),
+        )?)?)
+    };
+    let private = wrap(script_type, &format!("{origin}{privkey}{suffix}"))?;
+
+    Ok(Descriptors {
+        public,
+        private: with_checksum(&private)?,
+    })
+}
+
+fn wrap(script_type: ScriptType, key: &str) -> Result<String> {
+    Ok(match script_type {
+        ScriptType::P2pkh => format!("pkh({key})"),
+        ScriptType::P2shP2wpkh => format!("sh(wpkh({key}))"),
+        ScriptType::P2wpkh => format!("wpkh({key})"),
+        ScriptType::P2tr => format!("tr({key})"),
+        ScriptType::Evm => {
+            return Err(WalletBipError::Descriptor(
+                "EVM accounts have no output descriptor".to_string(),
+            ));
+        }
+    })
+}
+
+/// Appends the BIP380 checksum to a descriptor.
+///
+/// # Errors
+///
+/// Returns an error if the descriptor contains a character outside of the
+/// descriptor character set.
+pub fn with_checksum(descriptor: &str) -> Result<String> {
+    let checksum = checksum(descriptor)?;
+
+    Ok(format!("{descriptor}#{checksum}"))
+}
+
+fn checksum(descriptor: &str) -> Result<String> {
+    let mut symbols = expand(descriptor)?;
+    symbols.extend([0; CHECKSUM_LENGTH]);
+
+    let polymod = polymod(&symbols) ^ 1;
+
+    Ok((0..CHECKSUM_LENGTH)
+        .map(|i| {
+            let index = usize::try_from((polymod >> (5 * (CHECKSUM_LENGTH - 1 - i))) & 31)
+                .expect("masked to 5 bits");
+
+            char::from(CHECKSUM_CHARSET[index])
+        })
+        .collect())
+}
+
+fn expand(descriptor: &str) -> Result<Vec<u64>> {
+    let mut symbols = Vec::with_capacity(descriptor.len() * 4 / 3 + 1);
+    let mut groups = Vec::with_capacity(3);
+
+    for c in descriptor.chars() {
+        let position = INPUT_CHARSET.find(c).ok_or_else(|| {
+            WalletBipError::Descriptor(format!("invalid character {c:?} in {descriptor:?}"))
+        })?;
+        let position = u64::try_from(position).expect("charset has 95 characters");
+
+        symbols.push(position & 31);
+        groups.push(position >> 5);
+
+        if groups.len() == 3 {
+            symbols.push(groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+
+    match groups[..] {
+        [a] => symbols.push(a),
+        [a, b] => symbols.push(a * 3 + b),
+        _ => {}
+    }
+
+    Ok(symbols)
+}
+
+fn polymod(symbols: &[u64]) -> u64 {
+    const GEN: [u64; 5] = [
+        0xf5_dee5_1989,
+        0xa9_fdca_3312,
+        0x1b_ab10_e32d,
+        0x37_06b1_677a,
+        0x64_4d62_6ffd,
+    ];
+
+    let mut chk = 1u64;
+
+    for &value in symbols {
+        let top = chk >> 35;
+        chk = ((chk & 0x7_ffff_ffff) << 5) ^ value;
+
+        for (i, generator) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+
+    chk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("raw(deadbeef)", "raw(deadbeef)#89f8spxm")]
+    #[case(
+        "pkh([d34db33f/44'/0'/0']xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL/1/*)",
+        "pkh([d34db33f/44'/0'/0']xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL/1/*)#ml40v0wf"
+    )]
+    fn test_with_checksum(#[case] descriptor: &str, #[case] expected: &str) {
+        assert_eq!(with_checksum(descriptor).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_with_checksum_invalid_character() {
+        assert!(with_checksum("raw(dé)").is_err());
+    }
+
+    #[test]
+    fn test_key_origin() {
+        use std::str::FromStr;
+
+        let origin = KeyOrigin::new(
+            [0x73, 0xc5, 0xda, 0x0a],
+            DerivationPath::from_str("m/84'/0'/0'").unwrap(),
+        );
+
+        assert_eq!(origin.to_string(), "[73c5da0a/84h/0h/0h]");
+    }
+
+    #[test]
+    fn test_descriptors_debug() {
+        let descriptors = Descriptors {
+            public: Some("wpkh(xpub…/<0;1>/*)#checksum".to_string()),
+            private: "wpkh(xprv…/<0;1>/*)#checksum".to_string(),
+        };
+
+        let debug = format!("{descriptors:?}");
+
+        assert!(debug.contains("xpub"));
+        assert!(!debug.contains("xprv"));
+    }
+}
@@ -0,0 +1,717 @@
+use super::{KeyOrigin, checksum};
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::address::{self, WatchOnlyAddress};
+use crate::hd_wallet::path::parse_child;
+use crate::hd_wallet::{DerivationPath, Network, ScriptType};
+use crate::hex;
+use bip32::{ChildNumber, ExtendedKey, XPub};
+use core::ops::Range;
+use std::str::FromStr;
+
+/// The maximum number of keys of a multisig wrapped in pay to script hash, its
+/// redeem script must fit in 520 bytes.
+const MAX_SH_MULTI_KEYS: usize = 15;
+
+/// The maximum number of keys of a multisig outside of witness scripts, the
+/// key count is pushed with a small integer opcode (BIP383).
+const MAX_MULTI_KEYS: usize = 16;
+
+/// The maximum number of keys of a multisig in a witness script, the limit of
+/// `OP_CHECKMULTISIG`.
+const MAX_WSH_MULTI_KEYS: usize = 20;
+
+/// An output descriptor (BIP380–386) with extended keys, e.g.
+/// `wpkh([73c5da0a/84h/0h/0h]xpub…/<0;1>/*)#…`.
+///
+/// Supported scripts are `pkh`, `wpkh`, `sh(wpkh)`, `tr` without script tree,
+/// and `multi` or `sortedmulti` wrapped in `sh`, `wsh` or `sh(wsh)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Descriptor(Output);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Output {
+    Pkh(DescriptorKey),
+    Wpkh(DescriptorKey),
+    ShWpkh(DescriptorKey),
+    Tr(DescriptorKey),
+    ShMulti(Multi),
+    WshMulti(Multi),
+    ShWshMulti(Multi),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Multi {
+    threshold: usize,
+    keys: Vec<DescriptorKey>,
+    is_sorted: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DescriptorKey {
+    origin: Option<KeyOrigin>,
+    key: String,
+    is_private: bool,
+    steps: Vec<Step>,
+    wildcard: Option<Wildcard>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    Child(ChildNumber),
+
+    /// `<0;1>`, one alternative per descriptor the key expands to.
+    Multipath(Vec<ChildNumber>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Wildcard {
+    Unhardened,
+    Hardened,
+}
+
+impl Descriptor {
+    /// Whether addresses depend on an index, i.e. keys end with `/*`.
+    #[must_use]
+    pub fn is_ranged(&self) -> bool {
+        self.keys().any(|key| key.wildcard.is_some())
+    }
+
+    /// Whether keys have a `<…;…>` step, such descriptors must be split
+    /// before deriving addresses.
+    #[must_use]
+    pub fn is_multipath(&self) -> bool {
+        self.keys().any(|key| key.multipath_len().is_some())
+    }
+
+    /// Expands a multipath descriptor into one descriptor per alternative,
+    /// e.g. `<0;1>` gives the receive and the change descriptors.
+    #[must_use]
+    pub fn split_multipath(&self) -> Vec<Descriptor> {
+        let count = self
+            .keys()
+            .filter_map(DescriptorKey::multipath_len)
+            .max()
+            .unwrap_or(1);
+
+        (0..count)
+            .map(|i| Descriptor(self.0.map_keys(|key| key.select_multipath(i))))
+            .collect()
+    }
+
+    /// Derives the address at the given index, a descriptor which is not
+    /// ranged has a single address at index 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the descriptor is multipath, the index is out of
+    /// range, a key does not belong to the network or needs hardened
+    /// derivation from a public key.
+    pub fn address(&self, index: u32, network: Network) -> Result<String> {
+        if self.is_multipath() {
+            return Err(WalletBipError::Descriptor(
+                "multipath descriptor must be split first".to_string(),
+            ));
+        }
+
+        if !self.is_ranged() && index != 0 {
+            return Err(WalletBipError::Descriptor(format!(
+                "descriptor is not ranged, index {index} does not exist"
+            )));
+        }
+
+        let single = |key: &DescriptorKey, script_type| {
+            WatchOnlyAddress::new(&key.derive(index, network)?, script_type, network)
+                .map(|address| address.hash)
+        };
+
+        let script = |multi: &Multi| {
+            let mut pubkeys = multi
+                .keys
+                .iter()
+                .map(|key| key.derive(index, network).map(|pubkey| pubkey.to_bytes()))
+                .collect::<Result<Vec<_>>>()?;
+
+            if multi.is_sorted {
+                pubkeys.sort_unstable();
+            }
+
+            address::multisig_script(multi.threshold, &pubkeys)
+        };
+
+        match &self.0 {
+            Output::Pkh(key) => single(key, ScriptType::P2pkh),
+            Output::Wpkh(key) => single(key, ScriptType::P2wpkh),
+            Output::ShWpkh(key) => single(key, ScriptType::P2shP2wpkh),
+            Output::Tr(key) => single(key, ScriptType::P2tr),
+            Output::ShMulti(multi) => Ok(address::p2sh(&script(multi)?, network.address_params())),
+            Output::WshMulti(multi) => address::p2wsh(&script(multi)?, network.address_params()),
+            Output::ShWshMulti(multi) => Ok(address::p2sh_p2wsh(
+                &script(multi)?,
+                network.address_params(),
+            )),
+        }
+    }
+
+    /// Derives the addresses for a range of indices.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any address of the range cannot be derived, see
+    /// [`Descriptor::address`].
+    pub fn addresses(&self, indices: Range<u32>, network: Network) -> Result<Vec<String>> {
+        indices.map(|index| self.address(index, network)).collect()
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &DescriptorKey> {
+        match &self.0 {
+            Output::Pkh(key) | Output::Wpkh(key) | Output::ShWpkh(key) | Output::Tr(key) => {
+                core::slice::from_ref(key).iter()
+            }
+            Output::ShMulti(multi) | Output::WshMulti(multi) | Output::ShWshMulti(multi) => {
+                multi.keys.iter()
+            }
+        }
+    }
+}
+
+impl Output {
+    fn map_keys(&self, f: impl Fn(&DescriptorKey) -> DescriptorKey) -> Self {
+        let multi = |multi: &Multi| Multi {
+            threshold: multi.threshold,
+            keys: multi.keys.iter().map(&f).collect(),
+            is_sorted: multi.is_sorted,
+        };
+
+        match self {
+            Output::Pkh(key) => Output::Pkh(f(key)),
+            Output::Wpkh(key) => Output::Wpkh(f(key)),
+            Output::ShWpkh(key) => Output::ShWpkh(f(key)),
+            Output::Tr(key) => Output::Tr(f(key)),
+            Output::ShMulti(m) => Output::ShMulti(multi(m)),
+            Output::WshMulti(m) => Output::WshMulti(multi(m)),
+            Output::ShWshMulti(m) => Output::ShWshMulti(multi(m)),
+        }
+    }
+}
+
+impl DescriptorKey {
+    fn multipath_len(&self) -> Option<usize> {
+        self.steps.iter().find_map(|step| match step {
+            Step::Multipath(alternatives) => Some(alternatives.len()),
+            Step::Child(_) => None,
+        })
+    }
+
+    fn select_multipath(&self, i: usize) -> Self {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                Step::Multipath(alternatives) => Step::Child(alternatives[i]),
+                Step::Child(child) => Step::Child(*child),
+            })
+            .collect();
+
+        Self {
+            steps,
+            ..self.clone()
+        }
+    }
+
+    fn derive(&self, index: u32, network: Network) -> Result<XPub> {
+        let mut children = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                Step::Child(child) => Ok(*child),
+                Step::Multipath(_) => Err(WalletBipError::Descriptor(
+                    "multipath descriptor must be split first".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(wildcard) = self.wildcard {
+            children.push(ChildNumber::new(index, wildcard == Wildcard::Hardened)?);
+        }
+
+        if self.is_private {
+            let privkey = children
+                .into_iter()
+                .try_fold(network.parse_xprv(&self.key)?, |key, child| {
+                    key.derive_child(child)
+                })?;
+
+            return Ok(privkey.public_key());
+        }
+
+        if children.iter().any(ChildNumber::is_hardened) {
+            return Err(WalletBipError::HardenedFromPublic(format!(
+                "{} has hardened steps",
+                self.key
+            )));
+        }
+
+        Ok(children
+            .into_iter()
+            .try_fold(network.parse_xpub(&self.key)?, |key, child| {
+                key.derive_child(child)
+            })?)
+    }
+}
+
+impl FromStr for Descriptor {
+    type Err = WalletBipError;
+
+    /// Parses a descriptor, the checksum is optional but must be valid when
+    /// present.
+    fn from_str(descriptor: &str) -> Result<Self> {
+        let body = match descriptor.split_once('#') {
+            Some((body, expected)) => {
+                let actual = checksum(body)?;
+
+                if actual != expected {
+                    return Err(WalletBipError::InvalidChecksum(format!(
+                        "descriptor checksum {expected:?}, expected {actual:?}"
+                    )));
+                }
+
+                body
+            }
+            None => descriptor,
+        };
+
+        let output = parse_output(body)?;
+
+        let multipath_lens = Descriptor(output.clone())
+            .keys()
+            .filter_map(DescriptorKey::multipath_len)
+            .collect::<Vec<_>>();
+
+        if multipath_lens.windows(2).any(|lens| lens[0] != lens[1]) {
+            return Err(WalletBipError::Descriptor(
+                "multipath steps have different lengths".to_string(),
+            ));
+        }
+
+        Ok(Self(output))
+    }
+}
+
+fn parse_output(s: &str) -> Result<Output> {
+    let (name, args) = split_call(s)?;
+
+    Ok(match name {
+        "pkh" => Output::Pkh(parse_key(args)?),
+        "wpkh" => Output::Wpkh(parse_key(args)?),
+        "tr" if args.contains(',') => {
+            return Err(WalletBipError::Descriptor(
+                "taproot script trees are not supported".to_string(),
+            ));
+        }
+        "tr" => Output::Tr(parse_key(args)?),
+        "wsh" => Output::WshMulti(parse_multi_call(args, MAX_WSH_MULTI_KEYS)?),
+        "sh" => {
+            let (inner, inner_args) = split_call(args)?;
+
+            match inner {
+                "wpkh" => Output::ShWpkh(parse_key(inner_args)?),
+                "wsh" => Output::ShWshMulti(parse_multi_call(inner_args, MAX_WSH_MULTI_KEYS)?),
+                _ => {
+                    let multi = parse_multi_call(args, MAX_MULTI_KEYS)?;
+
+                    if multi.keys.len() > MAX_SH_MULTI_KEYS {
+                        return Err(WalletBipError::Descriptor(format!(
+                            "sh() multisig has more than {MAX_SH_MULTI_KEYS} keys"
+                        )));
+                    }
+
+                    Output::ShMulti(multi)
+                }
+            }
+        }
+        _ => {
+            return Err(WalletBipError::Descriptor(format!(
+                "unsupported script {name:?}"
+            )));
+        }
+    })
+}
+
+/// Splits `name(args)` into its name and arguments.
+fn split_call(s: &str) -> Result<(&str, &str)> {
+    s.split_once('(')
+        .and_then(|(name, rest)| Some((name, rest.strip_suffix(')')?)))
+        .ok_or_else(|| WalletBipError::Descriptor(format!("{s:?} is not a script expression")))
+}
+
+/// Parses `multi(…)` or `sortedmulti(…)` of at most `max_keys` keys, the limit
+/// of the script context it appears in.
+fn parse_multi_call(s: &str, max_keys: usize) -> Result<Multi> {
+    let (name, args) = split_call(s)?;
+
+    let is_sorted = match name {
+        "multi" => false,
+        "sortedmulti" => true,
+        _ => {
+            return Err(WalletBipError::Descriptor(format!(
+                "expected multi or sortedmulti, got {name:?}"
+            )));
+        }
+    };
+
+    let mut args = args.split(',');
+
+    let threshold = args
+        .next()
+        .and_then(|threshold| threshold.parse::<usize>().ok())
+        .ok_or_else(|| WalletBipError::Descriptor(format!("invalid threshold in {s:?}")))?;
+
+    let keys = args.map(parse_key).collect::<Result<Vec<_>>>()?;
+
+    if threshold == 0 || threshold > keys.len() || keys.len() > max_keys {
+        return Err(WalletBipError::Descriptor(format!(
+            "invalid multisig {threshold} of {}",
+            keys.len()
+        )));
+    }
+
+    Ok(Multi {
+        threshold,
+        keys,
+        is_sorted,
+    })
+}
+
+/// Parses a key expression, `[fingerprint/path]key/path/*`.
+fn parse_key(s: &str) -> Result<DescriptorKey> {
+    let (origin, rest) = match s.strip_prefix('[') {
+        Some(rest) => {
+            let (origin, rest) = rest.split_once(']').ok_or_else(|| {
+                WalletBipError::Descriptor(format!("unterminated key origin in {s:?}"))
+            })?;
+
+            (Some(parse_origin(origin)?), rest)
+        }
+        None => (None, s),
+    };
+
+    let mut segments = rest.split('/');
+    let key = segments.next().unwrap_or_default();
+
+    let extended = ExtendedKey::from_str(key)
+        .map_err(|_| WalletBipError::Descriptor(format!("invalid extended key {key:?}")))?;
+
+    let mut steps = Vec::new();
+    let mut wildcard = None;
+
+    for segment in segments {
+        if wildcard.is_some() {
+            return Err(WalletBipError::Descriptor(format!(
+                "wildcard must be the last step in {s:?}"
+            )));
+        }
+
+        match segment {
+            "*" => wildcard = Some(Wildcard::Unhardened),
+            "*'" | "*h" | "*H" => wildcard = Some(Wildcard::Hardened),
+            _ => match segment
+                .strip_prefix('<')
+                .and_then(|segment| segment.strip_suffix('>'))
+            {
+                Some(alternatives) => {
+                    if steps.iter().any(|step| matches!(step, Step::Multipath(_))) {
+                        return Err(WalletBipError::Descriptor(format!(
+                            "more than one multipath step in {s:?}"
+                        )));
+                    }
+
+                    let alternatives = alternatives
+                        .split(';')
+                        .map(|alternative| parse_child(alternative, s))
+                        .collect::<Result<Vec<_>>>()?;
+
+                    if alternatives.len() < 2 {
+                        return Err(WalletBipError::Descriptor(format!(
+                            "multipath step needs at least two alternatives in {s:?}"
+                        )));
+                    }
+
+                    steps.push(Step::Multipath(alternatives));
+                }
+                None => steps.push(Step::Child(parse_child(segment, s)?)),
+            },
+        }
+    }
+
+    Ok(DescriptorKey {
+        origin,
+        key: key.to_string(),
+        is_private: extended.prefix.is_private(),
+        steps,
+        wildcard,
+    })
+}
+
+/// Parses a key origin without its brackets, `73c5da0a/84h/0h/0h`.
+fn parse_origin(s: &str) -> Result<KeyOrigin> {
+    let (fingerprint, path) = s.split_at_checked(8).ok_or_else(|| {
+        WalletBipError::Descriptor(format!("invalid fingerprint in origin {s:?}"))
+    })?;
+
+    let fingerprint = hex::decode(fingerprint)
+        .ok()
+        .and_then(|fingerprint| fingerprint.try_into().ok())
+        .ok_or_else(|| {
+            WalletBipError::Descriptor(format!("invalid fingerprint in origin {s:?}"))
+        })?;
+
+    let path = if path.is_empty() || path.starts_with('/') {
+        DerivationPath::from_str(&format!("m{path}"))?
+    } else {
+        return Err(WalletBipError::Descriptor(format!(
+            "invalid path in origin {s:?}"
+        )));
+    };
+
+    Ok(KeyOrigin::new(fingerprint, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::{Multisig, MultisigScriptType, with_checksum};
+    use rstest::rstest;
+
+    const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    const XPUB_44: &str = "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj";
+
+    const XPUB_49: &str = "xpub6C6nQwHaWbSrzs5tZ1q7m5R9cPK9eYpNMFesiXsYrgc1P8bvLLAet9JfHjYXKjToD8cBRswJXXbbFpXgwsswVPAZzKMa1jUp2kVkGVUaJa7";
+
+    const XPUB_84: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+    const XPUB_86: &str = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+
+    const XPUB_OTHER: &str = "xpub6DNacWSbNjuHdUKQ9n7cutFhieeSTsvAhgTvmZUm79jqGRbCFgmedGKExRZHEfSzzCdxRTkXTGr34oRWruVGS8ie12imQJ4dhVCQAHWFstY";
+
+    #[rstest]
+    #[case(format!("pkh({XPUB_44}/0/*)"), "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA")]
+    #[case(format!("sh(wpkh({XPUB_49}/0/*))"), "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf")]
+    #[case(
+        format!("wpkh([73c5da0a/84h/0h/0h]{XPUB_84}/0/*)"),
+        "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+    )]
+    #[case(
+        format!("tr({XPUB_86}/0/*)"),
+        "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+    )]
+    #[case(
+        format!("wpkh({ROOT}/84h/0h/0h/0/*)"),
+        "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+    )]
+    fn test_single_key_address(#[case] descriptor: String, #[case] hash: &str) {
+        let descriptor = Descriptor::from_str(&with_checksum(&descriptor).unwrap()).unwrap();
+
+        assert_eq!(descriptor.address(0, Network::Bitcoin).unwrap(), hash);
+    }
+
+    #[rstest]
+    #[case(
+        format!("sh(multi(2,{XPUB_44}/0/*,{XPUB_84}/0/*,{XPUB_OTHER}/0/*))"),
+        ["3Q7HwTneLPy5hg1znSgyZPa1pJoL38tHay", "3PqMmTL5bvDrmgrh62dUYh8AgddUnew4eo"]
+    )]
+    #[case(
+        format!("sh(sortedmulti(2,{XPUB_44}/0/*,{XPUB_84}/0/*,{XPUB_OTHER}/0/*))"),
+        ["3AFhPe68ShjFsMzgd4tQxvgrfe3TTY2zv7", "3QLovKcyrFUThTqqUSvaMZhLARtViT2MZw"]
+    )]
+    #[case(
+        format!("wsh(multi(2,{XPUB_44}/0/*,{XPUB_84}/0/*,{XPUB_OTHER}/0/*))"),
+        [
+            "bc1qwetm7jwgq40eezj8m9zdmm0y3yevyzlwnayarm5afp94wvj6jessz6rzxs",
+            "bc1qp4ldhazn0kkvf7zwnnhahucq2lxkwqrxm7vv9v64jhhvwktg9sgq58t72m"
+        ]
+    )]
+    #[case(
+        format!("wsh(sortedmulti(2,{XPUB_OTHER}/0/*,{XPUB_44}/0/*,{XPUB_84}/0/*))"),
+        [
+            "bc1q4wts68qu0gpaplew3j5tk9ywpa260qlkp0fnt99t5fxpf5gv26csx6un95",
+            "bc1qsannh3v72nsrjxaukxnwevx4w39hd7z9z7z2xrjyzgvefgjw75wszpsvhr"
+        ]
+    )]
+    #[case(
+        format!("sh(wsh(multi(2,{XPUB_44}/0/*,{XPUB_84}/0/*,{XPUB_OTHER}/0/*)))"),
+        ["3AeAyvyjsnwNaKR5NqemhNKZZLptPDsNhw", "3GM9VQVhPyJSQ8yxsqC2mfSmWB8QU9xJLt"]
+    )]
+    fn test_multisig_addresses(#[case] descriptor: String, #[case] hashes: [&str; 2]) {
+        let descriptor = Descriptor::from_str(&descriptor).unwrap();
+
+        assert_eq!(
+            descriptor.addresses(0..2, Network::Bitcoin).unwrap(),
+            hashes
+        );
+    }
+
+    #[test]
+    fn test_multipath() {
+        let descriptor = Descriptor::from_str(
+            &with_checksum(&format!("wpkh([73c5da0a/84h/0h/0h]{XPUB_84}/<0;1>/*)")).unwrap(),
+        )
+        .unwrap();
+
+        assert!(descriptor.is_multipath());
+        assert!(descriptor.address(0, Network::Bitcoin).is_err());
+
+        let [receive, change] = descriptor.split_multipath().try_into().unwrap();
+
+        assert_eq!(
+            receive.addresses(0..2, Network::Bitcoin).unwrap(),
+            [
+                "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+                "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+            ]
+        );
+        assert_eq!(
+            change.address(0, Network::Bitcoin).unwrap(),
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+        );
+    }
+
+    #[test]
+    fn test_regtest_address() {
+        let descriptor = Descriptor::from_str("wpkh([73c5da0a/84h/1h/0h]tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/0/*)").unwrap();
+
+        assert_eq!(
+            descriptor.address(0, Network::Regtest).unwrap(),
+            "bcrt1q6rz28mcfaxtmd6v789l9rrlrusdprr9pz3cppk"
+        );
+        assert!(matches!(
+            descriptor.address(0, Network::Bitcoin),
+            Err(WalletBipError::NetworkMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_not_ranged() {
+        let descriptor = Descriptor::from_str(&format!("wpkh({XPUB_84}/0/0)")).unwrap();
+
+        assert!(!descriptor.is_ranged());
+        assert_eq!(
+            descriptor.address(0, Network::Bitcoin).unwrap(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert!(descriptor.address(1, Network::Bitcoin).is_err());
+    }
+
+    #[test]
+    fn test_hardened_from_public() {
+        let descriptor = Descriptor::from_str(&format!("wpkh({XPUB_84}/0/*h)")).unwrap();
+
+        assert!(matches!(
+            descriptor.address(0, Network::Bitcoin),
+            Err(WalletBipError::HardenedFromPublic(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_checksum() {
+        let mut descriptor = with_checksum(&format!("wpkh({XPUB_84}/0/*)")).unwrap();
+        descriptor.pop();
+        descriptor.push('0');
+
+        assert!(matches!(
+            Descriptor::from_str(&descriptor),
+            Err(WalletBipError::InvalidChecksum(_))
+        ));
+    }
+
+    fn cosigners(count: usize) -> Vec<String> {
+        (0..u32::try_from(count).unwrap())
+            .map(|index| {
+                crate::hd_wallet::derive(
+                    ROOT,
+                    &DerivationPath::new(vec![ChildNumber::new(index, true).unwrap()]),
+                    Network::Bitcoin,
+                )
+                .unwrap()
+                .pubkey
+                .to_string()
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case("wsh(sortedmulti", ")", MultisigScriptType::P2wsh)]
+    #[case("sh(wsh(sortedmulti", "))", MultisigScriptType::P2shP2wsh)]
+    fn test_max_segwit_multisig(
+        #[case] prefix: &str,
+        #[case] suffix: &str,
+        #[case] script_type: MultisigScriptType,
+    ) {
+        let cosigners = cosigners(MAX_WSH_MULTI_KEYS);
+        let keys = cosigners
+            .iter()
+            .map(|cosigner| format!("{cosigner}/0/*"))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let descriptor =
+            Descriptor::from_str(&with_checksum(&format!("{prefix}(15,{keys}){suffix}")).unwrap())
+                .unwrap();
+
+        let cosigners = cosigners.iter().map(String::as_str).collect::<Vec<_>>();
+        let multisig = Multisig::new(15, &cosigners, script_type, Network::Bitcoin).unwrap();
+
+        for index in 0..2 {
+            assert_eq!(
+                descriptor.address(index, Network::Bitcoin).unwrap(),
+                multisig
+                    .prepare_address(&[
+                        ChildNumber::new(0, false).unwrap(),
+                        ChildNumber::new(index, false).unwrap()
+                    ])
+                    .unwrap()
+                    .hash()
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("wsh(multi", ")", MAX_WSH_MULTI_KEYS + 1)]
+    #[case("sh(wsh(multi", "))", MAX_WSH_MULTI_KEYS + 1)]
+    #[case("sh(multi", ")", MAX_SH_MULTI_KEYS + 1)]
+    fn test_too_many_multisig_keys(
+        #[case] prefix: &str,
+        #[case] suffix: &str,
+        #[case] count: usize,
+    ) {
+        let keys = cosigners(count)
+            .iter()
+            .map(|cosigner| format!("{cosigner}/0/*"))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        assert!(matches!(
+            Descriptor::from_str(&format!("{prefix}(1,{keys}){suffix}")),
+            Err(WalletBipError::Descriptor(_))
+        ));
+    }
+
+    #[rstest]
+    #[case(format!("wpkh({XPUB_84}/0/*"))]
+    #[case(format!("raw({XPUB_84})"))]
+    #[case(format!("wpkh({XPUB_84}/*/0)"))]
+    #[case(format!("wpkh({XPUB_84}/<0>/*)"))]
+    #[case(format!("wpkh({XPUB_84}/<0;1>/<0;1>/*)"))]
+    #[case(format!("wpkh([73c5da/84h]{XPUB_84}/0/*)"))]
+    #[case(format!("wpkh([73c5da0a84h]{XPUB_84}/0/*)"))]
+    #[case(format!("tr({XPUB_86},pk({XPUB_84}))"))]
+    #[case(format!("wsh(multi(3,{XPUB_44}/0/*,{XPUB_84}/0/*))"))]
+    #[case(format!("wsh(multi(0,{XPUB_44}/0/*))"))]
+    #[case(format!("wsh(pkh({XPUB_44}/0/*))"))]
+    #[case(format!("wsh(multi(1,{XPUB_44}/<0;1>/*,{XPUB_84}/<0;1;2>/*))"))]
+    #[case("wpkh(xpub123)".to_string())]
+    fn test_parse_invalid(#[case] descriptor: String) {
+        assert!(Descriptor::from_str(&descriptor).is_err());
+    }
+}
@@ -0,0 +1,622 @@
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::b44::Bip44;
+use crate::hd_wallet::{
+    Chain, Coin, DerivationPath, ExtendedPrivKey, ExtendedPubPrivKey, Network, PathStep,
+    ScriptType, SeedScheme, WALLET_PRESETS, WalletPreset, electrum_seed_type,
+    prepare_electrum_seed, prepare_root, prepare_seed,
+};
+use bip32::ChildNumber;
+use core::fmt;
+use core::hash::BuildHasher;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// The number of consecutive unused addresses after which BIP44 account
+/// discovery considers a chain exhausted.
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// The derivation path an address was found at, with every wallet preset
+/// that derives this address there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathMatch {
+    path: DerivationPath,
+    presets: Vec<WalletPreset>,
+}
+
+impl PathMatch {
+    #[must_use]
+    pub fn path(&self) -> &DerivationPath {
+        &self.path
+    }
+
+    /// The presets sharing the path and the script type, in catalog order.
+    #[must_use]
+    pub fn presets(&self) -> &[WalletPreset] {
+        &self.presets
+    }
+}
+
+/// Searches the wallet presets for the derivation path of an address of a
+/// mnemonic.
+///
+/// Only the presets the coin can encode addresses for are tried, the
+/// Ethereum presets for EVM coins and the Bitcoin presets otherwise. A BIP39
+/// mnemonic is searched with the BIP39 presets and an Electrum seed with the
+/// Electrum preset of its type. Every preset is tried for the accounts
+/// `0..accounts` (presets without an account level only for account 0), both
+/// the receive and the change chain and the address indices `0..gap`.
+///
+/// Returns `None` if no preset derives the address within those bounds.
+///
+/// # Errors
+///
+/// Returns an error if the coin has no known address format, the mnemonic is
+/// neither a BIP39 phrase nor an Electrum seed or a derivation fails.
+pub fn find_address_path(
+    mnemonic: &[&str],
+    passphrase: &str,
+    address: &str,
+    coin: &Coin,
+    network: Network,
+    accounts: u32,
+    gap: u32,
+) -> Result<Option<PathMatch>> {
+    let Some(script_type) = coin.script_type() else {
+        return Err(WalletBipError::InvalidCoin(format!(
+            "{} addresses cannot be encoded",
+            coin.symbol()
+        )));
+    };
+    let is_evm = script_type == ScriptType::Evm;
+
+    let electrum = electrum_seed_type(mnemonic);
+    let bip39 = match prepare_seed(mnemonic, passphrase) {
+        Ok(seed) => Some(seed),
+        Err(error) if electrum.is_none() => return Err(error),
+        Err(_) => None,
+    };
+
+    let bip39_root = bip39
+        .map(|seed| ExtendedPrivKey::parse(&prepare_root(seed, network)?, network))
+        .transpose()?;
+    let electrum_root = electrum
+        .map(|_| {
+            let seed = prepare_electrum_seed(mnemonic, passphrase)?;
+
+            ExtendedPrivKey::parse(&prepare_root(seed, network)?, network)
+        })
+        .transpose()?;
+
+    let root = |preset: &WalletPreset| match preset.seed() {
+        SeedScheme::Bip39 => bip39_root.as_ref(),
+        SeedScheme::Electrum(seed_type) if Some(seed_type) == electrum => electrum_root.as_ref(),
+        SeedScheme::Electrum(_) => None,
+    };
+
+    let presets = WALLET_PRESETS
+        .iter()
+        .filter(|preset| coin.supports(preset.script_type()) && root(preset).is_some());
+
+    for preset in presets.clone() {
+        let Some(root) = root(preset) else {
+            continue;
+        };
+        let accounts = if preset.steps().contains(&PathStep::Account) {
+            accounts
+        } else {
+            accounts.min(1)
+        };
+        let chains: &[Chain] = if preset
+            .steps()
+            .iter()
+            .any(|step| matches!(step, PathStep::Change { .. }))
+        {
+            &[Chain::External, Chain::Internal]
+        } else {
+            &[Chain::External]
+        };
+
+        for account in 0..accounts {
+            for &chain in chains {
+                let addresses = preset.derive_addresses(root, coin, account, chain, 0..gap)?;
+
+                for (index, derived) in (0..gap).zip(addresses) {
+                    let derived = derived?;
+
+                    let is_match = if is_evm {
+                        derived.hash().eq_ignore_ascii_case(address)
+                    } else {
+                        derived.hash() == address
+                    };
+
+                    if !is_match {
+                        continue;
+                    }
+
+                    let path = preset.path(coin, account, chain, index, network)?;
+                    let presets = presets
+                        .clone()
+                        .filter(|other| {
+                            other.seed() == preset.seed()
+                                && other.script_type() == preset.script_type()
+                                && other
+                                    .path(coin, account, chain, index, network)
+                                    .is_ok_and(|other| other == path)
+                        })
+                        .copied()
+                        .collect();
+
+                    return Ok(Some(PathMatch { path, presets }));
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Decides whether an address has been used, e.g. by querying a block
+/// explorer or a local index.
+pub trait UsageOracle {
+    /// # Errors
+    ///
+    /// Returns an error if the usage of the address cannot be determined.
+    fn is_used(&self, address: &str) -> Result<bool>;
+}
+
+impl<T: UsageOracle + ?Sized> UsageOracle for &T {
+    fn is_used(&self, address: &str) -> Result<bool> {
+        (**self).is_used(address)
+    }
+}
+
+impl<S: BuildHasher> UsageOracle for HashSet<String, S> {
+    fn is_used(&self, address: &str) -> Result<bool> {
+        Ok(self.contains(address))
+    }
+}
+
+/// An offline [`UsageOracle`], a set of known used addresses.
+///
+/// EVM addresses are compared case-insensitively, so checksummed and
+/// lowercase addresses match.
+#[derive(Clone, Debug, Default)]
+pub struct UsedAddresses(HashSet<String>);
+
+impl UsedAddresses {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the addresses from a file with one address per line, blank lines
+    /// and lines starting with `#` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+
+        Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect())
+    }
+
+    pub fn insert(&mut self, address: &str) {
+        self.0.insert(normalize(address));
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn normalize(address: &str) -> String {
+    if address.starts_with("0x") {
+        address.to_ascii_lowercase()
+    } else {
+        address.to_owned()
+    }
+}
+
+impl<'a> FromIterator<&'a str> for UsedAddresses {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut addresses = Self::new();
+
+        for address in iter {
+            addresses.insert(address);
+        }
+
+        addresses
+    }
+}
+
+impl UsageOracle for UsedAddresses {
+    fn is_used(&self, address: &str) -> Result<bool> {
+        Ok(self.0.contains(&normalize(address)))
+    }
+}
+
+/// An account found by [`AccountDiscovery`].
+pub struct DiscoveredAccount {
+    account: u32,
+    extended: ExtendedPubPrivKey,
+    last_used_index: u32,
+}
+
+impl DiscoveredAccount {
+    #[must_use]
+    pub fn account(&self) -> u32 {
+        self.account
+    }
+
+    /// The account keys `m/44'/coin'/account'`.
+    #[must_use]
+    pub fn extended(&self) -> &ExtendedPubPrivKey {
+        &self.extended
+    }
+
+    /// The highest used index of the receive chain.
+    #[must_use]
+    pub fn last_used_index(&self) -> u32 {
+        self.last_used_index
+    }
+}
+
+/// BIP44 account discovery.
+///
+/// Accounts are scanned in order, the receive chain of every account is
+/// scanned until `gap_limit` consecutive addresses are unused. Discovery
+/// stops at the first account without any used address.
+pub struct AccountDiscovery<O> {
+    oracle: O,
+    gap_limit: u32,
+}
+
+impl<O: UsageOracle> AccountDiscovery<O> {
+    /// Discovery with the [`DEFAULT_GAP_LIMIT`].
+    #[must_use]
+    pub fn new(oracle: O) -> Self {
+        Self {
+            oracle,
+            gap_limit: DEFAULT_GAP_LIMIT,
+        }
+    }
+
+    #[must_use]
+    pub fn with_gap_limit(self, gap_limit: u32) -> Self {
+        Self { gap_limit, ..self }
+    }
+
+    /// Discovers the used accounts of a root key, see
+    /// [`Bip44::prepare_account_extended_key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network, is not a root key, a derivation fails or the oracle fails.
+    pub fn discover<C: Bip44>(
+        &self,
+        root_key: &str,
+        coin: &Coin,
+        network: Network,
+    ) -> Result<Vec<DiscoveredAccount>> {
+        let mut accounts = Vec::new();
+
+        for account in 0..ChildNumber::HARDENED_FLAG {
+            let extended = C::prepare_account_extended_key(root_key, coin, account, network)?;
+            let chain = C::derive_extended_key(extended.privkey(), Chain::External)?;
+
+            let Some(last_used_index) = self.scan::<C>(chain.privkey())? else {
+                break;
+            };
+
+            accounts.push(DiscoveredAccount {
+                account,
+                extended,
+                last_used_index,
+            });
+        }
+
+        Ok(accounts)
+    }
+
+    /// The highest used index of a chain, `None` if the first `gap_limit`
+    /// addresses are unused.
+    fn scan<C: Bip44>(&self, chain: &ExtendedPrivKey) -> Result<Option<u32>> {
+        let mut last_used_index = None;
+        let mut unused = 0;
+
+        let addresses = C::derive_addresses(chain, 0..ChildNumber::HARDENED_FLAG)?;
+
+        for (index, address) in (0..).zip(addresses) {
+            if unused >= self.gap_limit {
+                break;
+            }
+
+            if self.oracle.is_used(address?.hash())? {
+                last_used_index = Some(index);
+                unused = 0;
+            } else {
+                unused += 1;
+            }
+        }
+
+        Ok(last_used_index)
+    }
+}
+
+/// Displays the path followed by the names of the presets, e.g.
+/// `m/84'/0'/1'/0/0 (Ledger Live, Trezor, Samourai)`.
+impl fmt::Display for PathMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (", self.path)?;
+
+        for (position, preset) in self.presets.iter().enumerate() {
+            if position > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{}", preset.name())?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::CoinRegistry;
+    use rstest::rstest;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn find(
+        mnemonic: &str,
+        address: &str,
+        coin: &Coin,
+        accounts: u32,
+        gap: u32,
+    ) -> Option<PathMatch> {
+        let mnemonic = mnemonic.split(' ').collect::<Vec<_>>();
+
+        find_address_path(
+            &mnemonic,
+            "",
+            address,
+            coin,
+            Network::Bitcoin,
+            accounts,
+            gap,
+        )
+        .unwrap()
+    }
+
+    fn known(symbol: &str) -> Coin {
+        CoinRegistry::default().by_symbol(symbol).unwrap().clone()
+    }
+
+    const ELECTRUM_SEGWIT: &str =
+        "bitter grass shiver impose acquire brush forget axis eager alone wine silver";
+
+    #[rstest]
+    #[case(
+        MNEMONIC,
+        "bc1qku0qh0mc00y8tk0n65x2tqw4trlspak0fnjmfz",
+        Coin::BTC,
+        "m/84'/0'/1'/0/0 (Ledger Live, Trezor, Samourai)"
+    )]
+    #[case(
+        MNEMONIC,
+        "1C2J2rNFRpbGVLtmQ39LdMgvmYkB1o7Kdo",
+        Coin::BTC,
+        "m/0'/1'/1' (Bitcoin Core)"
+    )]
+    #[case(
+        MNEMONIC,
+        "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+        Coin::BTC,
+        "m/44'/0'/0'/0/0 (Mycelium, Coinomi)"
+    )]
+    #[case(
+        MNEMONIC,
+        "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+        Coin::ETH,
+        "m/44'/60'/0'/0/0 (Metamask)"
+    )]
+    #[case(
+        MNEMONIC,
+        "ltc1qjmxnz78nmc8nq77wuxh25n2es7rzm5c2rkk4wh",
+        known("LTC"),
+        "m/84'/2'/0'/0/0 (Ledger Live, Trezor, Exodus, Samourai)"
+    )]
+    #[case(
+        ELECTRUM_SEGWIT,
+        "bc1qdy94n2q5qcp0kg7v9yzwe6wvfkhnvyzje7nx2p",
+        Coin::BTC,
+        "m/0'/1/0 (Electrum segwit)"
+    )]
+    fn test_find_address_path(
+        #[case] mnemonic: &str,
+        #[case] address: &str,
+        #[case] coin: Coin,
+        #[case] expected: &str,
+    ) {
+        let found = find(mnemonic, address, &coin, 2, 3).unwrap();
+
+        assert_eq!(found.to_string(), expected);
+    }
+
+    #[test]
+    fn test_find_address_path_ethereum_classic() {
+        let etc = CoinRegistry::default().by_symbol("ETC").unwrap().clone();
+
+        let found = find(
+            MNEMONIC,
+            "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+            &etc,
+            1,
+            1,
+        );
+
+        assert!(found.is_none());
+    }
+
+    #[test]
+    fn test_find_address_path_unsupported_coin() {
+        let mnemonic = MNEMONIC.split(' ').collect::<Vec<_>>();
+        let sol = CoinRegistry::default().by_symbol("SOL").unwrap().clone();
+
+        assert!(matches!(
+            find_address_path(
+                &mnemonic,
+                "",
+                "LUWPbpM43E2p7ZSh8cyTBEkvpHmr3cB8Ez",
+                &sol,
+                Network::Bitcoin,
+                1,
+                1
+            ),
+            Err(WalletBipError::InvalidCoin(_))
+        ));
+    }
+
+    mod account_discovery {
+        use super::*;
+        use crate::hd_wallet::Client;
+        use crate::hd_wallet::b44::Ethereum;
+
+        const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+        fn address(account: u32, index: u32) -> String {
+            let extended =
+                Ethereum::prepare_account_extended_key(ROOT, &Coin::ETH, account, Network::Bitcoin)
+                    .unwrap();
+            let chain = Ethereum::derive_extended_key(extended.privkey(), Chain::External).unwrap();
+
+            Ethereum::derive_address(chain.privkey(), index)
+                .unwrap()
+                .hash()
+                .to_owned()
+        }
+
+        fn discover(used: &UsedAddresses, gap_limit: u32) -> Vec<(u32, u32)> {
+            AccountDiscovery::new(used)
+                .with_gap_limit(gap_limit)
+                .discover::<Ethereum>(ROOT, &Coin::ETH, Network::Bitcoin)
+                .unwrap()
+                .iter()
+                .map(|account| (account.account(), account.last_used_index()))
+                .collect()
+        }
+
+        #[test]
+        fn test_discover_accounts() {
+            let used = [
+                "0x9858effd232b4033e47d90003d41ec34ecaeda94".to_owned(),
+                address(0, 20),
+                address(1, 19),
+                address(3, 0),
+            ];
+            let used = used.iter().map(String::as_str).collect();
+
+            assert_eq!(discover(&used, DEFAULT_GAP_LIMIT), [(0, 20), (1, 19)]);
+            assert_eq!(discover(&used, 20), discover(&used, DEFAULT_GAP_LIMIT));
+            assert_eq!(discover(&used, 19), [(0, 0)]);
+        }
+
+        #[test]
+        fn test_discover_no_accounts() {
+            assert!(discover(&UsedAddresses::new(), DEFAULT_GAP_LIMIT).is_empty());
+        }
+
+        #[test]
+        fn test_hash_set_oracle() {
+            let used = HashSet::from([address(0, 3)]);
+
+            let accounts = AccountDiscovery::new(&used)
+                .discover::<Ethereum>(ROOT, &Coin::ETH, Network::Bitcoin)
+                .unwrap();
+
+            assert_eq!(accounts.len(), 1);
+            assert_eq!(
+                accounts[0].extended().origin().unwrap().to_string(),
+                "[73c5da0a/44h/60h/0h]"
+            );
+        }
+
+        #[test]
+        fn test_used_addresses_from_file() {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos();
+            let path = std::env::temp_dir().join(format!(
+                "wallet_bips_used_addresses_{}_{nanos}.txt",
+                std::process::id()
+            ));
+            std::fs::write(
+                &path,
+                "# exported from a block explorer\n\n0x9858EfFD232B4033E47d90003D41EC34EcaEda94\n  bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu \n",
+            )
+            .unwrap();
+
+            let used = UsedAddresses::from_file(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(used.len(), 2);
+            assert!(
+                used.is_used("0x9858effd232b4033e47d90003d41ec34ecaeda94")
+                    .unwrap()
+            );
+            assert!(
+                used.is_used("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")
+                    .unwrap()
+            );
+            assert!(
+                !used
+                    .is_used("bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g")
+                    .unwrap()
+            );
+            assert!(matches!(
+                UsedAddresses::from_file(&path),
+                Err(crate::errors::WalletBipError::ReadInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_find_address_path_beyond_gap() {
+        assert!(
+            find(
+                MNEMONIC,
+                "1C2J2rNFRpbGVLtmQ39LdMgvmYkB1o7Kdo",
+                &Coin::BTC,
+                1,
+                1
+            )
+            .is_none()
+        );
+        assert!(
+            find(
+                MNEMONIC,
+                "bc1qku0qh0mc00y8tk0n65x2tqw4trlspak0fnjmfz",
+                &Coin::BTC,
+                1,
+                3
+            )
+            .is_none()
+        );
+    }
+}
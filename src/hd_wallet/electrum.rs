@@ -0,0 +1,207 @@
+use crate::Result;
+use crate::errors::WalletBipError;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::canonical_combining_class;
+
+/// The number of PBKDF2 iterations stretching an Electrum seed.
+const PBKDF2_ROUNDS: u32 = 2048;
+
+/// The ranges of CJK characters, Electrum drops the whitespace between them.
+const CJK_INTERVALS: [(u32, u32); 29] = [
+    (0x4e00, 0x9fff),   // CJK Unified Ideographs
+    (0x3400, 0x4dbf),   // CJK Unified Ideographs Extension A
+    (0x20000, 0x2a6df), // CJK Unified Ideographs Extension B
+    (0x2a700, 0x2b73f), // CJK Unified Ideographs Extension C
+    (0x2b740, 0x2b81f), // CJK Unified Ideographs Extension D
+    (0xf900, 0xfaff),   // CJK Compatibility Ideographs
+    (0x2f800, 0x2fa1d), // CJK Compatibility Ideographs Supplement
+    (0x3190, 0x319f),   // Kanbun
+    (0x2e80, 0x2eff),   // CJK Radicals Supplement
+    (0x2f00, 0x2fdf),   // CJK Radicals
+    (0x31c0, 0x31ef),   // CJK Strokes
+    (0x2ff0, 0x2fff),   // Ideographic Description Characters
+    (0xe0100, 0xe01ef), // Variation Selectors Supplement
+    (0x3100, 0x312f),   // Bopomofo
+    (0x31a0, 0x31bf),   // Bopomofo Extended
+    (0xff00, 0xffef),   // Halfwidth and Fullwidth Forms
+    (0x3040, 0x309f),   // Hiragana
+    (0x30a0, 0x30ff),   // Katakana
+    (0x31f0, 0x31ff),   // Katakana Phonetic Extensions
+    (0x1b000, 0x1b0ff), // Kana Supplement
+    (0xac00, 0xd7af),   // Hangul Syllables
+    (0x1100, 0x11ff),   // Hangul Jamo
+    (0xa960, 0xa97f),   // Hangul Jamo Extended A
+    (0xd7b0, 0xd7ff),   // Hangul Jamo Extended B
+    (0x3130, 0x318f),   // Hangul Compatibility Jamo
+    (0xa4d0, 0xa4ff),   // Lisu
+    (0x16f00, 0x16f9f), // Miao
+    (0xa000, 0xa48f),   // Yi Syllables
+    (0xa490, 0xa4cf),   // Yi Radicals
+];
+
+/// The wallet an Electrum seed restores, Electrum encodes it in the seed
+/// version instead of a BIP39 checksum.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum ElectrumSeedType {
+    /// Legacy P2PKH addresses at `m/change/index`, version prefix `01`.
+    Standard,
+
+    /// Native segwit P2WPKH addresses at `m/0'/change/index`, version prefix
+    /// `100`.
+    Segwit,
+}
+
+impl ElectrumSeedType {
+    fn version_prefix(self) -> &'static str {
+        match self {
+            ElectrumSeedType::Standard => "01",
+            ElectrumSeedType::Segwit => "100",
+        }
+    }
+}
+
+/// Detects the type of an Electrum seed from its version, `None` if the
+/// phrase is not an Electrum seed of a supported type.
+///
+/// Only seeds of Electrum 2.0 and later are recognised. The phrase is
+/// normalized as Electrum does, so seeds in other languages are recognised
+/// too.
+#[must_use]
+pub fn electrum_seed_type(mnemonic: &[&str]) -> Option<ElectrumSeedType> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(b"Seed version").ok()?;
+    mac.update(normalize(&mnemonic.join(" ")).as_bytes());
+
+    let version = crate::hex::encode(&mac.finalize().into_bytes(), false).ok()?;
+
+    [ElectrumSeedType::Standard, ElectrumSeedType::Segwit]
+        .into_iter()
+        .find(|seed_type| version.starts_with(seed_type.version_prefix()))
+}
+
+/// Builds the seed of an Electrum mnemonic and an optional passphrase, the
+/// root key of the Electrum presets of
+/// [`WalletPreset`](crate::hd_wallet::WalletPreset) is built from it.
+///
+/// # Errors
+///
+/// Returns an error if the phrase is not an Electrum seed of a supported type.
+pub fn prepare_electrum_seed(mnemonic: &[&str], passphrase: &str) -> Result<[u8; 64]> {
+    if electrum_seed_type(mnemonic).is_none() {
+        return Err(WalletBipError::UnknownSeedVersion);
+    }
+
+    let mut salt = b"electrum".to_vec();
+    salt.extend_from_slice(normalize(passphrase).as_bytes());
+
+    let mut seed = [0u8; 64];
+    pbkdf2::pbkdf2_hmac::<Sha512>(
+        normalize(&mnemonic.join(" ")).as_bytes(),
+        &salt,
+        PBKDF2_ROUNDS,
+        &mut seed,
+    );
+
+    Ok(seed)
+}
+
+/// Normalizes the text as Electrum's `normalize_text`: NFKD, lowercase, no
+/// accents, single spaces and no spaces between CJK characters.
+fn normalize(text: &str) -> String {
+    let text = text
+        .nfkd()
+        .collect::<String>()
+        .to_lowercase()
+        .chars()
+        .filter(|&c| canonical_combining_class(c) == 0)
+        .collect::<String>();
+
+    let chars = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect::<Vec<_>>();
+
+    chars
+        .iter()
+        .enumerate()
+        .filter(|&(i, &c)| {
+            // Single spaces are never first or last, after the join.
+            !(c == ' ' && is_cjk(chars[i - 1]) && is_cjk(chars[i + 1]))
+        })
+        .map(|(_, &c)| c)
+        .collect()
+}
+
+fn is_cjk(c: char) -> bool {
+    CJK_INTERVALS
+        .iter()
+        .any(|&(start, end)| (start..=end).contains(&u32::from(c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::{Network, prepare_root};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(
+        "cycle rocket west magnet parrot shuffle foot correct salt library feed song",
+        Some(ElectrumSeedType::Standard)
+    )]
+    #[case(
+        "bitter grass shiver impose acquire brush forget axis eager alone wine silver",
+        Some(ElectrumSeedType::Segwit)
+    )]
+    #[case(
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        None
+    )]
+    fn test_electrum_seed_type(#[case] mnemonic: &str, #[case] expected: Option<ElectrumSeedType>) {
+        let mnemonic = mnemonic.split(' ').collect::<Vec<_>>();
+
+        assert_eq!(electrum_seed_type(&mnemonic), expected);
+    }
+
+    #[test]
+    fn test_prepare_electrum_seed() {
+        let mnemonic =
+            "Cycle rocket west magnet parrot shuffle foot correct salt library feed song"
+                .split(' ')
+                .collect::<Vec<_>>();
+
+        let seed = prepare_electrum_seed(&mnemonic, "").unwrap();
+
+        assert_eq!(
+            prepare_root(seed, Network::Bitcoin).unwrap(),
+            "xprv9s21ZrQH143K32jECVM729vWgGq4mUDJCk1ozqAStTphzQtCTuoFmFafNoG1g55iCnBTXUzz3zWnDb5CVLGiFvmaZjuazHDL8a81cPQ8KL6"
+        );
+    }
+
+    #[test]
+    fn test_prepare_electrum_seed_bip39() {
+        let mnemonic = ["abandon"; 11]
+            .into_iter()
+            .chain(["about"])
+            .collect::<Vec<_>>();
+
+        assert!(matches!(
+            prepare_electrum_seed(&mnemonic, ""),
+            Err(WalletBipError::UnknownSeedVersion)
+        ));
+    }
+
+    #[rstest]
+    #[case("  Cycle\tROCKET  west ", "cycle rocket west")]
+    #[case("Café Über\u{3000}naïve", "cafe uber naive")]
+    #[case("ﬁre ｗｅｓｔ", "fire west")]
+    #[case("なのか ひろい しなん", "なのかひろいしなん")]
+    #[case("中 文 abc 字", "中文 abc 字")]
+    #[case("각 난", "각난")]
+    fn test_normalize(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(normalize(text), expected.nfkd().collect::<String>());
+    }
+}
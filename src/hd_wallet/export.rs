@@ -0,0 +1,160 @@
+//! JSON serialization of derivation results, secrets are grouped under a
+//! `secret` object so watch-only exports can drop a single key.
+
+use crate::hd_wallet::{
+    Address, ExtendedPubPrivKey, KeyOrigin, MultisigAddress, WatchOnlyAddress, WatchOnlyExtendedKey,
+};
+use serde::{Serialize, Serializer};
+
+#[derive(Serialize)]
+struct PrivkeySecret<'a> {
+    privkey: &'a str,
+}
+
+#[derive(Serialize)]
+struct AddressJson<'a> {
+    hash: &'a str,
+    pubkey: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<PrivkeySecret<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExtendedKeyJson<'a> {
+    pubkey: String,
+    origin: Option<&'a KeyOrigin>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<PrivkeySecret<'a>>,
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AddressJson {
+            hash: &self.hash,
+            pubkey: &self.pubkey,
+            secret: Some(PrivkeySecret {
+                privkey: &self.privkey,
+            }),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for WatchOnlyAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AddressJson {
+            hash: &self.hash,
+            pubkey: &self.pubkey,
+            secret: None,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for ExtendedPubPrivKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let privkey = self.privkey.to_string();
+
+        ExtendedKeyJson {
+            pubkey: self.pubkey.to_string(),
+            origin: self.origin.as_ref(),
+            secret: Some(PrivkeySecret { privkey: &privkey }),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for WatchOnlyExtendedKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ExtendedKeyJson {
+            pubkey: self.pubkey.to_string(),
+            origin: self.origin.as_ref(),
+            secret: None,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for KeyOrigin {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for MultisigAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct MultisigAddressJson<'a> {
+            hash: &'a str,
+            script: &'a str,
+        }
+
+        MultisigAddressJson {
+            hash: &self.hash,
+            script: &self.script,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hd_wallet::b84::{Bip84, NativeSegwit};
+    use crate::hd_wallet::{Chain, Client, Coin, Network};
+    use serde_json::json;
+
+    const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    #[test]
+    fn test_serialize_extended_key() {
+        let account =
+            NativeSegwit::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin)
+                .unwrap();
+
+        let expected = json!({
+            "pubkey": "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs",
+            "origin": "[73c5da0a/84h/0h/0h]",
+            "secret": {
+                "privkey": "zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE"
+            }
+        });
+
+        assert_eq!(serde_json::to_value(&account).unwrap(), expected);
+
+        let mut watch_only = expected;
+        watch_only.as_object_mut().unwrap().remove("secret");
+
+        assert_eq!(
+            serde_json::to_value(account.watch_only()).unwrap(),
+            watch_only
+        );
+    }
+
+    #[test]
+    fn test_serialize_address() {
+        let account =
+            NativeSegwit::prepare_account_extended_key(ROOT, &Coin::BTC, 0, Network::Bitcoin)
+                .unwrap();
+        let extended =
+            NativeSegwit::derive_extended_key(account.privkey(), Chain::External).unwrap();
+        let address = NativeSegwit::derive_address(extended.privkey(), 0).unwrap();
+
+        let expected = json!({
+            "hash": "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            "pubkey": "0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c",
+            "secret": {
+                "privkey": "KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d"
+            }
+        });
+
+        assert_eq!(serde_json::to_value(&address).unwrap(), expected);
+
+        let mut watch_only = expected;
+        watch_only.as_object_mut().unwrap().remove("secret");
+
+        assert_eq!(
+            serde_json::to_value(address.watch_only()).unwrap(),
+            watch_only
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use crate::Result;
+use crate::hd_wallet::{ExtendedKeyType, ROOT_KEY_DEPTH, check_depth, detect_key_type, slip132};
+use crate::hex;
+use bip32::{ChildNumber, ExtendedKey, KeyFingerprint, PublicKey, PublicKeyBytes, XPrv, XPub};
+use core::fmt;
+use std::str::FromStr;
+
+/// The decoded fields of an extended key. Private keys are reduced to their
+/// public key, so the description can be shown without revealing secrets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyInfo {
+    version: String,
+    is_private: bool,
+    is_mainnet: bool,
+    key_type: ExtendedKeyType,
+    depth: u8,
+    parent_fingerprint: KeyFingerprint,
+    child_number: ChildNumber,
+    chain_code: [u8; 32],
+    public_key: PublicKeyBytes,
+    fingerprint: KeyFingerprint,
+}
+
+impl KeyInfo {
+    /// The SLIP-132 version, e.g. `zpub`.
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    #[must_use]
+    pub fn is_private(&self) -> bool {
+        self.is_private
+    }
+
+    /// Whether the version is a Bitcoin mainnet version, test networks share
+    /// their versions.
+    #[must_use]
+    pub fn is_mainnet(&self) -> bool {
+        self.is_mainnet
+    }
+
+    /// The script type the version implies.
+    #[must_use]
+    pub fn key_type(&self) -> ExtendedKeyType {
+        self.key_type
+    }
+
+    #[must_use]
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    #[must_use]
+    pub fn parent_fingerprint(&self) -> KeyFingerprint {
+        self.parent_fingerprint
+    }
+
+    #[must_use]
+    pub fn child_number(&self) -> ChildNumber {
+        self.child_number
+    }
+
+    #[must_use]
+    pub fn is_hardened(&self) -> bool {
+        self.child_number.is_hardened()
+    }
+
+    #[must_use]
+    pub fn chain_code(&self) -> [u8; 32] {
+        self.chain_code
+    }
+
+    /// The compressed public key.
+    #[must_use]
+    pub fn public_key(&self) -> PublicKeyBytes {
+        self.public_key
+    }
+
+    /// The fingerprint of the key itself, the master fingerprint for a master
+    /// key.
+    #[must_use]
+    pub fn fingerprint(&self) -> KeyFingerprint {
+        self.fingerprint
+    }
+}
+
+/// Decodes an extended key of any SLIP-132 Bitcoin version.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be parsed or its version is unknown.
+pub fn inspect_key(key: &str) -> Result<KeyInfo> {
+    let key_type = detect_key_type(key)?;
+    let extended = ExtendedKey::from_str(key)?;
+
+    let version = extended.prefix.as_str().to_string();
+    let is_private = extended.prefix.is_private();
+    let is_mainnet = slip132::version_network(extended.prefix)?.is_mainnet();
+
+    let xpub = XPub::try_from(extended)?;
+    let attrs = xpub.attrs();
+
+    Ok(KeyInfo {
+        version,
+        is_private,
+        is_mainnet,
+        key_type,
+        depth: attrs.depth,
+        parent_fingerprint: attrs.parent_fingerprint,
+        child_number: attrs.child_number,
+        chain_code: attrs.chain_code,
+        public_key: xpub.public_key().to_bytes(),
+        fingerprint: xpub.fingerprint(),
+    })
+}
+
+/// The fingerprint of the master key of a seed, as found in key origins.
+///
+/// # Errors
+///
+/// Returns an error if the seed has an unsupported length.
+pub fn master_fingerprint_from_seed<S: AsRef<[u8]>>(seed: S) -> Result<KeyFingerprint> {
+    Ok(XPrv::new(seed)?.public_key().fingerprint())
+}
+
+/// The fingerprint of a master key, private or public.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be parsed, its version is unknown or it
+/// is not a master key.
+pub fn master_fingerprint(root_key: &str) -> Result<KeyFingerprint> {
+    let info = inspect_key(root_key)?;
+
+    check_depth(info.depth, ROOT_KEY_DEPTH)?;
+
+    Ok(info.fingerprint)
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    f.write_str(&hex::encode(bytes, false).map_err(|_| fmt::Error)?)
+}
+
+/// Displays one field per line, e.g. for support tooling.
+impl fmt::Display for KeyInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let network = if self.is_mainnet {
+            "mainnet"
+        } else {
+            "testnet"
+        };
+
+        writeln!(f, "version: {} ({network})", self.version)?;
+        writeln!(f, "script type: {:?}", self.key_type)?;
+        writeln!(f, "depth: {}", self.depth)?;
+        write!(f, "parent fingerprint: ")?;
+        write_hex(f, &self.parent_fingerprint)?;
+        writeln!(f)?;
+        writeln!(f, "child number: {}", self.child_number)?;
+        write!(f, "chain code: ")?;
+        write_hex(f, &self.chain_code)?;
+        writeln!(f)?;
+        write!(f, "public key: ")?;
+        write_hex(f, &self.public_key)?;
+        writeln!(f)?;
+        write!(f, "fingerprint: ")?;
+        write_hex(f, &self.fingerprint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::WalletBipError;
+    use rstest::rstest;
+
+    const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    const ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    const ZPRV: &str = "zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE";
+
+    #[test]
+    fn test_inspect_key() {
+        let info = inspect_key(ZPUB).unwrap();
+
+        assert_eq!(info.version(), "zpub");
+        assert!(!info.is_private());
+        assert!(info.is_mainnet());
+        assert_eq!(info.key_type(), ExtendedKeyType::P2wpkh);
+        assert_eq!(info.depth(), 3);
+        assert_eq!(info.parent_fingerprint(), [0x7e, 0xf3, 0x2b, 0xdb]);
+        assert_eq!(info.child_number(), ChildNumber::new(0, true).unwrap());
+        assert!(info.is_hardened());
+        assert_eq!(
+            hex::encode(&info.chain_code(), false).unwrap(),
+            "4a53a0ab21b9dc95869c4e92a161194e03c0ef3ff5014ac692f433c4765490fc"
+        );
+        assert_eq!(
+            hex::encode(&info.public_key(), false).unwrap(),
+            "02707a62fdacc26ea9b63b1c197906f56ee0180d0bcf1966e1a2da34f5f3a09a9b"
+        );
+        assert_eq!(info.fingerprint(), [0xfd, 0x13, 0xaa, 0xc9]);
+    }
+
+    #[test]
+    fn test_inspect_private_key() {
+        let info = inspect_key(ZPRV).unwrap();
+
+        assert_eq!(info.version(), "zprv");
+        assert!(info.is_private());
+        assert_eq!(info.public_key(), inspect_key(ZPUB).unwrap().public_key());
+        assert!(!info.to_string().contains(ZPRV));
+    }
+
+    #[test]
+    fn test_inspect_testnet_key() {
+        let info = inspect_key("tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd").unwrap();
+
+        assert!(!info.is_mainnet());
+        assert_eq!(info.key_type(), ExtendedKeyType::P2pkh);
+        assert_eq!(info.fingerprint(), [0x73, 0xc5, 0xda, 0x0a]);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            inspect_key(ZPUB).unwrap().to_string(),
+            "version: zpub (mainnet)
+script type: P2wpkh
+depth: 3
+parent fingerprint: 7ef32bdb
+child number: 0'
+chain code: 4a53a0ab21b9dc95869c4e92a161194e03c0ef3ff5014ac692f433c4765490fc
+public key: 02707a62fdacc26ea9b63b1c197906f56ee0180d0bcf1966e1a2da34f5f3a09a9b
+fingerprint: fd13aac9"
+        );
+    }
+
+    #[test]
+    fn test_master_fingerprint_from_seed() {
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").unwrap();
+
+        assert_eq!(
+            master_fingerprint_from_seed(seed).unwrap(),
+            [0x73, 0xc5, 0xda, 0x0a]
+        );
+    }
+
+    #[rstest]
+    #[case(ROOT)]
+    #[case(
+        "xpub661MyMwAqRbcFkPHucMnrGNzDwb6teAX1RbKQmqtEF8kK3Z7LZ59qafCjB9eCRLiTVG3uxBxgKvRgbubRhqSKXnGGb1aoaqLrpMBDrVxga8"
+    )]
+    fn test_master_fingerprint(#[case] root: &str) {
+        assert_eq!(master_fingerprint(root).unwrap(), [0x73, 0xc5, 0xda, 0x0a]);
+    }
+
+    #[test]
+    fn test_master_fingerprint_not_root() {
+        assert!(matches!(
+            master_fingerprint(ZPUB),
+            Err(WalletBipError::InvalidDepth {
+                expected: 0,
+                actual: 3
+            })
+        ));
+    }
+}
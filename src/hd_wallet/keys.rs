@@ -0,0 +1,336 @@
+use crate::Result;
+use crate::hd_wallet::{AddressParams, DerivationPath, Network, ScriptType, slip132};
+use bip32::{ChildNumber, KeyFingerprint, Prefix, XPrv, XPub};
+use core::fmt;
+
+/// A parsed extended private key, keeps the version it is serialized with and
+/// the network it belongs to so it can be derived from without re-parsing.
+///
+/// The addresses of its children are encoded with the address parameters of
+/// the key, the Bitcoin ones of the network unless the key was derived for
+/// another coin.
+#[derive(Clone)]
+pub struct ExtendedPrivKey {
+    key: XPrv,
+    prefix: Prefix,
+    network: Network,
+    params: AddressParams,
+}
+
+/// A parsed extended public key, keeps the version it is serialized with and
+/// the network it belongs to so it can be derived from without re-parsing.
+///
+/// See [`ExtendedPrivKey`] for the address parameters.
+#[derive(Clone)]
+pub struct ExtendedPubKey {
+    key: XPub,
+    prefix: Prefix,
+    network: Network,
+    params: AddressParams,
+}
+
+impl ExtendedPrivKey {
+    /// Wraps a key, serialized with the SLIP-132 prefix of the script type.
+    #[must_use]
+    pub fn new(key: XPrv, script_type: ScriptType, network: Network) -> Self {
+        let (_, prefix) = network.prefixes(script_type);
+
+        Self {
+            key,
+            prefix,
+            network,
+            params: network.address_params(),
+        }
+    }
+
+    /// Parses a key and checks that its version belongs to the network.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed or does not belong to the
+    /// network.
+    pub fn parse(key: &str, network: Network) -> Result<Self> {
+        let extended = network.parse_extended_key(key)?;
+
+        Ok(Self {
+            prefix: extended.prefix,
+            key: XPrv::try_from(extended)?,
+            network,
+            params: network.address_params(),
+        })
+    }
+
+    #[must_use]
+    pub fn depth(&self) -> u8 {
+        self.key.attrs().depth
+    }
+
+    #[must_use]
+    pub fn child_number(&self) -> ChildNumber {
+        self.key.attrs().child_number
+    }
+
+    #[must_use]
+    pub fn parent_fingerprint(&self) -> KeyFingerprint {
+        self.key.attrs().parent_fingerprint
+    }
+
+    /// The fingerprint of the key itself, what key origins of its children
+    /// refer to.
+    #[must_use]
+    pub fn fingerprint(&self) -> KeyFingerprint {
+        self.key.public_key().fingerprint()
+    }
+
+    #[must_use]
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// The public key, serialized with the public counterpart of the version.
+    #[must_use]
+    pub fn public_key(&self) -> ExtendedPubKey {
+        ExtendedPubKey {
+            key: self.key.public_key(),
+            prefix: slip132::public_prefix(self.prefix),
+            network: self.network,
+            params: self.params,
+        }
+    }
+
+    #[must_use]
+    pub fn address_params(&self) -> AddressParams {
+        self.params
+    }
+
+    /// Re-encodes the key with the SLIP-132 prefix of the script type.
+    #[must_use]
+    pub fn with_script_type(self, script_type: ScriptType) -> Self {
+        let (_, prefix) = self.network.prefixes(script_type);
+
+        Self { prefix, ..self }
+    }
+
+    /// Encodes the addresses of the children of the key with the address
+    /// parameters of another coin.
+    #[must_use]
+    pub fn with_address_params(self, params: AddressParams) -> Self {
+        Self { params, ..self }
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the derivation fails.
+    pub fn derive_child(&self, child_number: ChildNumber) -> Result<Self> {
+        Ok(Self {
+            key: self.key.derive_child(child_number)?,
+            ..self.clone()
+        })
+    }
+
+    /// Derives the key at a path relative to this key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the derivation fails.
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self> {
+        Ok(Self {
+            key: path
+                .iter()
+                .try_fold(self.key.clone(), |key, child| key.derive_child(child))?,
+            ..self.clone()
+        })
+    }
+
+    pub(crate) fn as_xprv(&self) -> &XPrv {
+        &self.key
+    }
+
+    pub(crate) fn with_prefix(self, prefix: Prefix) -> Self {
+        Self { prefix, ..self }
+    }
+}
+
+impl ExtendedPubKey {
+    /// Wraps a key, serialized with the SLIP-132 prefix of the script type.
+    #[must_use]
+    pub fn new(key: XPub, script_type: ScriptType, network: Network) -> Self {
+        let (prefix, _) = network.prefixes(script_type);
+
+        Self {
+            key,
+            prefix,
+            network,
+            params: network.address_params(),
+        }
+    }
+
+    /// Parses a key and checks that its version belongs to the network, an
+    /// extended private key is turned into its public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed or does not belong to the
+    /// network.
+    pub fn parse(key: &str, network: Network) -> Result<Self> {
+        let extended = network.parse_extended_key(key)?;
+
+        Ok(Self {
+            prefix: slip132::public_prefix(extended.prefix),
+            key: XPub::try_from(extended)?,
+            network,
+            params: network.address_params(),
+        })
+    }
+
+    #[must_use]
+    pub fn depth(&self) -> u8 {
+        self.key.attrs().depth
+    }
+
+    #[must_use]
+    pub fn child_number(&self) -> ChildNumber {
+        self.key.attrs().child_number
+    }
+
+    #[must_use]
+    pub fn parent_fingerprint(&self) -> KeyFingerprint {
+        self.key.attrs().parent_fingerprint
+    }
+
+    #[must_use]
+    pub fn fingerprint(&self) -> KeyFingerprint {
+        self.key.fingerprint()
+    }
+
+    #[must_use]
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    #[must_use]
+    pub fn address_params(&self) -> AddressParams {
+        self.params
+    }
+
+    /// Re-encodes the key with the SLIP-132 prefix of the script type.
+    #[must_use]
+    pub fn with_script_type(self, script_type: ScriptType) -> Self {
+        let (prefix, _) = self.network.prefixes(script_type);
+
+        Self { prefix, ..self }
+    }
+
+    /// Encodes the addresses of the children of the key with the address
+    /// parameters of another coin.
+    #[must_use]
+    pub fn with_address_params(self, params: AddressParams) -> Self {
+        Self { params, ..self }
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the child is hardened or the derivation fails.
+    pub fn derive_child(&self, child_number: ChildNumber) -> Result<Self> {
+        Ok(Self {
+            key: self.key.derive_child(child_number)?,
+            ..self.clone()
+        })
+    }
+
+    pub(crate) fn as_xpub(&self) -> &XPub {
+        &self.key
+    }
+
+    pub(crate) fn with_prefix(self, prefix: Prefix) -> Self {
+        Self { prefix, ..self }
+    }
+}
+
+impl fmt::Display for ExtendedPrivKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.key.to_string(self.prefix))
+    }
+}
+
+impl fmt::Display for ExtendedPubKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.key.to_string(self.prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    #[rstest]
+    #[case(ROOT, Network::Bitcoin)]
+    #[case(
+        "zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE",
+        Network::Bitcoin
+    )]
+    #[case(
+        "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd",
+        Network::Regtest
+    )]
+    fn test_parse_display(#[case] key: &str, #[case] network: Network) {
+        assert_eq!(
+            ExtendedPrivKey::parse(key, network).unwrap().to_string(),
+            key
+        );
+    }
+
+    #[test]
+    fn test_derive_path() {
+        let root = ExtendedPrivKey::parse(ROOT, Network::Bitcoin).unwrap();
+
+        let account = root
+            .derive_path(&DerivationPath::from_str("m/84'/0'/0'").unwrap())
+            .unwrap()
+            .with_script_type(ScriptType::P2wpkh);
+
+        assert_eq!(account.depth(), 3);
+        assert_eq!(account.child_number(), ChildNumber::new(0, true).unwrap());
+        assert_eq!(
+            account.public_key().to_string(),
+            "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs"
+        );
+        assert_eq!(root.fingerprint(), [0x73, 0xc5, 0xda, 0x0a]);
+
+        let change = account
+            .derive_child(ChildNumber::new(1, false).unwrap())
+            .unwrap();
+
+        assert_eq!(change.parent_fingerprint(), account.fingerprint());
+        assert_eq!(
+            change.public_key().to_string(),
+            account
+                .public_key()
+                .derive_child(ChildNumber::new(1, false).unwrap())
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_network_mismatch() {
+        assert!(ExtendedPrivKey::parse(ROOT, Network::Testnet).is_err());
+        assert!(ExtendedPubKey::parse(ROOT, Network::Testnet).is_err());
+    }
+
+    #[test]
+    fn test_parse_private_as_public() {
+        let root = ExtendedPrivKey::parse(ROOT, Network::Bitcoin).unwrap();
+
+        assert_eq!(
+            ExtendedPubKey::parse(ROOT, Network::Bitcoin)
+                .unwrap()
+                .to_string(),
+            root.public_key().to_string()
+        );
+    }
+}
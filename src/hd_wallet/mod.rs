@@ -1,42 +1,192 @@
 mod address;
-mod b32;
-mod b44;
+pub mod b32;
+pub mod b44;
+pub mod b49;
+pub mod b84;
+pub mod b86;
+mod batch;
+mod coin;
+mod descriptor;
+mod discovery;
+mod electrum;
+#[cfg(feature = "serde")]
+mod export;
+mod inspect;
+mod keys;
+mod multisig;
+mod network;
+mod origin;
+mod path;
+mod preset;
+mod slip132;
 
 use crate::Result;
 use crate::errors::WalletBipError;
-use address::Address;
 use bip32::secp256k1::ecdsa::{SigningKey, VerifyingKey};
-use bip32::{ChildNumber, ExtendedPrivateKey, ExtendedPublicKey, Prefix, XPrv};
+use bip32::{ChildNumber, ExtendedPrivateKey, ExtendedPublicKey, XPrv};
 use bip39::Mnemonic;
-use eyre::eyre;
-use std::str::FromStr;
+use core::fmt;
+use core::ops::Range;
 
+pub use address::{Address, AddressParams, ScriptType, WatchOnlyAddress};
+pub use batch::{Addresses, WatchOnlyAddresses};
+pub use coin::{Coin, CoinRegistry};
+pub use descriptor::{Descriptor, Descriptors, KeyOrigin, with_checksum};
+pub use discovery::{
+    AccountDiscovery, DEFAULT_GAP_LIMIT, DiscoveredAccount, PathMatch, UsageOracle, UsedAddresses,
+    find_address_path,
+};
+pub use electrum::{ElectrumSeedType, electrum_seed_type, prepare_electrum_seed};
+pub use inspect::{KeyInfo, inspect_key, master_fingerprint, master_fingerprint_from_seed};
+pub use keys::{ExtendedPrivKey, ExtendedPubKey};
+pub use multisig::{
+    Multisig, MultisigAddress, MultisigScriptType, prepare_bip45_extended_key,
+    prepare_bip48_account_extended_key,
+};
+pub use network::Network;
+pub use origin::OriginVerification;
+pub use path::{Chain, DerivationPath, derive, derive_from_seed};
+pub use preset::{PathStep, SeedScheme, WALLET_PRESETS, WalletPreset};
+pub use slip132::{ExtendedKeyType, convert_key, detect_key_type};
+
+/// Builds the BIP39 seed for a mnemonic and an optional passphrase.
+///
+/// # Errors
+///
+/// Returns an error if the mnemonic is not a valid BIP39 phrase, unknown
+/// words and invalid checksums are reported as such.
 pub fn prepare_seed(mnemonic: &[&str], passphrase: &str) -> Result<[u8; 64]> {
     use std::str::FromStr;
-    let mnemonic = Mnemonic::from_str(&mnemonic.join(" "))?;
+    let phrase = Mnemonic::from_str(&mnemonic.join(" "))
+        .map_err(|e| crate::mnemonic::parse_error(mnemonic, e))?;
 
-    Ok(mnemonic.to_seed(passphrase))
+    Ok(phrase.to_seed(passphrase))
 }
 
-pub fn prepare_root<S: AsRef<[u8]>>(seed: S) -> Result<String> {
+/// Builds the BIP32 root extended private key for a seed, serialized for
+/// the given network.
+///
+/// # Errors
+///
+/// Returns an error if the seed has an unsupported length.
+pub fn prepare_root<S: AsRef<[u8]>>(seed: S, network: Network) -> Result<String> {
     let root = XPrv::new(seed)?;
+    let (_, priv_prefix) = network.prefixes(ScriptType::P2pkh);
 
-    Ok(root.to_string(Prefix::XPRV).to_string())
+    Ok(root.to_string(priv_prefix).to_string())
 }
 
+/// The public and private extended keys at one derivation path, with the
+/// origin of the keys when they were derived from a master key.
+///
+/// Displayed as the extended public key. With the `serde` feature it
+/// serializes as
+/// `{"pubkey": "xpub…", "origin": "[73c5da0a/44h/0h/0h]", "secret": {"privkey": "xprv…"}}`,
+/// `origin` is `null` when unknown. [`ExtendedPubPrivKey::watch_only`] drops
+/// the private key, its export has no `secret`.
 pub struct ExtendedPubPrivKey {
-    pubkey: String,
-    privkey: String,
+    pub(crate) pubkey: ExtendedPubKey,
+    pub(crate) privkey: ExtendedPrivKey,
+    pub(crate) origin: Option<KeyOrigin>,
 }
 
 impl ExtendedPubPrivKey {
+    #[must_use]
     pub fn new(
         pubkey: &ExtendedPublicKey<VerifyingKey>,
         privkey: &ExtendedPrivateKey<SigningKey>,
+        network: Network,
+    ) -> Self {
+        Self::with_script_type(pubkey, privkey, ScriptType::P2pkh, network)
+    }
+
+    /// Serializes the keys with the SLIP-132 prefixes of the script type.
+    #[must_use]
+    pub fn with_script_type(
+        pubkey: &ExtendedPublicKey<VerifyingKey>,
+        privkey: &ExtendedPrivateKey<SigningKey>,
+        script_type: ScriptType,
+        network: Network,
     ) -> Self {
         Self {
-            pubkey: pubkey.to_string(Prefix::XPUB).to_string(),
-            privkey: privkey.to_string(Prefix::XPRV).to_string(),
+            pubkey: ExtendedPubKey::new(pubkey.clone(), script_type, network),
+            privkey: ExtendedPrivKey::new(privkey.clone(), script_type, network),
+            origin: None,
+        }
+    }
+
+    /// Attaches the master key fingerprint and the derivation path of the
+    /// keys, they are exported with descriptors.
+    #[must_use]
+    pub fn with_origin(mut self, origin: KeyOrigin) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    #[must_use]
+    pub fn pubkey(&self) -> &ExtendedPubKey {
+        &self.pubkey
+    }
+
+    #[must_use]
+    pub fn privkey(&self) -> &ExtendedPrivKey {
+        &self.privkey
+    }
+
+    #[must_use]
+    pub fn origin(&self) -> Option<&KeyOrigin> {
+        self.origin.as_ref()
+    }
+
+    /// Drops the private key.
+    #[must_use]
+    pub fn watch_only(self) -> WatchOnlyExtendedKey {
+        WatchOnlyExtendedKey {
+            pubkey: self.pubkey,
+            origin: self.origin,
+        }
+    }
+}
+
+/// An extended public key with its origin, it has no private part.
+///
+/// Displayed as the extended public key. With the `serde` feature it
+/// serializes as `{"pubkey": "xpub…", "origin": "[73c5da0a/44h/0h/0h]"}`.
+pub struct WatchOnlyExtendedKey {
+    pub(crate) pubkey: ExtendedPubKey,
+    pub(crate) origin: Option<KeyOrigin>,
+}
+
+impl WatchOnlyExtendedKey {
+    #[must_use]
+    pub fn pubkey(&self) -> &ExtendedPubKey {
+        &self.pubkey
+    }
+
+    #[must_use]
+    pub fn origin(&self) -> Option<&KeyOrigin> {
+        self.origin.as_ref()
+    }
+}
+
+impl fmt::Display for ExtendedPubPrivKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pubkey)
+    }
+}
+
+impl fmt::Display for WatchOnlyExtendedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pubkey)
+    }
+}
+
+impl From<ExtendedPrivKey> for ExtendedPubPrivKey {
+    fn from(privkey: ExtendedPrivKey) -> Self {
+        Self {
+            pubkey: privkey.public_key(),
+            privkey,
+            origin: None,
         }
     }
 }
@@ -46,22 +196,220 @@ pub trait Client {
 
     const IS_HARDENED_ADDRESSES: bool;
 
-    fn prepare_address(extended_key: &str, index: u32) -> Result<Address> {
-        let extended = XPrv::from_str(extended_key)?;
+    const SCRIPT_TYPE: ScriptType = ScriptType::P2pkh;
 
-        if extended.attrs().depth != Self::EXTENDED_KEY_DEPTH {
-            return Err(WalletBipError::Unexpected(eyre!(
-                "Key depth must be {}",
-                Self::EXTENDED_KEY_DEPTH
-            )));
-        }
+    /// Derives the address with the given index from the client's extended key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key has an unexpected depth or the derivation
+    /// fails.
+    fn derive_address(extended: &ExtendedPrivKey, index: u32) -> Result<Address> {
+        check_depth(extended.depth(), Self::EXTENDED_KEY_DEPTH)?;
 
-        let privkey =
-            extended.derive_child(ChildNumber::new(index, Self::IS_HARDENED_ADDRESSES)?)?;
+        let privkey = extended
+            .as_xprv()
+            .derive_child(ChildNumber::new(index, Self::IS_HARDENED_ADDRESSES)?)?;
         let pubkey = privkey.public_key();
 
-        Address::new(&pubkey, &privkey)
+        Address::from_keys(
+            pubkey.public_key(),
+            privkey.private_key(),
+            Self::SCRIPT_TYPE,
+            extended.address_params(),
+        )
+    }
+
+    /// Derives the address with the given index from the client's extended
+    /// public key, the result has no private key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client uses hardened address indices or the key
+    /// has an unexpected depth.
+    fn derive_watch_only_address(
+        extended: &ExtendedPubKey,
+        index: u32,
+    ) -> Result<WatchOnlyAddress> {
+        check_unhardened(Self::IS_HARDENED_ADDRESSES)?;
+        check_depth(extended.depth(), Self::EXTENDED_KEY_DEPTH)?;
+
+        let pubkey = extended
+            .as_xpub()
+            .derive_child(ChildNumber::new(index, false)?)?;
+
+        WatchOnlyAddress::from_key(
+            pubkey.public_key(),
+            Self::SCRIPT_TYPE,
+            extended.address_params(),
+        )
+    }
+
+    /// Derives the addresses of a range of indices lazily, with the `rayon`
+    /// feature they can be derived in parallel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key has an unexpected depth.
+    fn derive_addresses(extended: &ExtendedPrivKey, indexes: Range<u32>) -> Result<Addresses> {
+        check_depth(extended.depth(), Self::EXTENDED_KEY_DEPTH)?;
+
+        Ok(Addresses::new(
+            extended,
+            indexes,
+            Self::IS_HARDENED_ADDRESSES,
+            Self::SCRIPT_TYPE,
+        ))
+    }
+
+    /// Derives the watch-only addresses of a range of indices lazily, see
+    /// [`Client::derive_addresses`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client uses hardened address indices or the key
+    /// has an unexpected depth.
+    fn derive_watch_only_addresses(
+        extended: &ExtendedPubKey,
+        indexes: Range<u32>,
+    ) -> Result<WatchOnlyAddresses> {
+        check_unhardened(Self::IS_HARDENED_ADDRESSES)?;
+        check_depth(extended.depth(), Self::EXTENDED_KEY_DEPTH)?;
+
+        Ok(WatchOnlyAddresses::new(
+            extended,
+            indexes,
+            Self::SCRIPT_TYPE,
+        ))
+    }
+
+    /// Parses the extended key and derives the address with the given index,
+    /// see [`Client::derive_address`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be parsed, does not belong to the
+    /// network or has an unexpected depth.
+    fn prepare_address(extended_key: &str, index: u32, network: Network) -> Result<Address> {
+        Self::derive_address(&ExtendedPrivKey::parse(extended_key, network)?, index)
+    }
+
+    /// Parses the extended public key and derives the address with the given
+    /// index, see [`Client::derive_watch_only_address`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client uses hardened address indices, the key
+    /// cannot be parsed, does not belong to the network or has an unexpected
+    /// depth.
+    fn prepare_watch_only_address(
+        extended_key: &str,
+        index: u32,
+        network: Network,
+    ) -> Result<WatchOnlyAddress> {
+        check_unhardened(Self::IS_HARDENED_ADDRESSES)?;
+
+        Self::derive_watch_only_address(&ExtendedPubKey::parse(extended_key, network)?, index)
     }
+
+    /// Builds the output descriptors of an account or chain extended key of
+    /// the client, e.g. `wpkh([73c5da0a/84h/0h/0h]xpub…/<0;1>/*)#…`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client has no descriptor or the keys have an
+    /// unexpected depth.
+    fn prepare_descriptors(extended: &ExtendedPubPrivKey) -> Result<Descriptors> {
+        descriptor::descriptors(
+            extended,
+            Self::SCRIPT_TYPE,
+            Self::EXTENDED_KEY_DEPTH,
+            Self::IS_HARDENED_ADDRESSES,
+        )
+    }
+}
+
+/// Rejects watch-only derivation for clients with hardened address indices.
+fn check_unhardened(is_hardened_addresses: bool) -> Result<()> {
+    if is_hardened_addresses {
+        return Err(WalletBipError::HardenedFromPublic(
+            "addresses of this client use hardened indices, an extended private key is required"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The depth of a master key.
+const ROOT_KEY_DEPTH: u8 = 0;
+
+fn check_depth(depth: u8, expected: u8) -> Result<()> {
+    if depth != expected {
+        return Err(WalletBipError::InvalidDepth {
+            expected,
+            actual: depth,
+        });
+    }
+
+    Ok(())
+}
+
+/// The depth of an account key, `m/purpose'/coin'/account'`.
+const ACCOUNT_KEY_DEPTH: u8 = 3;
+
+/// Derives the account keys `m/purpose'/coin'/account'` from a root key,
+/// serialized with the SLIP-132 version of the script type.
+///
+/// Test networks use coin type 1 for every coin, EVM chains keep their coin
+/// type.
+fn derive_account(
+    root: &ExtendedPrivKey,
+    purpose: u32,
+    coin: &Coin,
+    account: u32,
+    script_type: ScriptType,
+) -> Result<ExtendedPubPrivKey> {
+    check_depth(root.depth(), ROOT_KEY_DEPTH)?;
+
+    coin.check_script_type(script_type)?;
+
+    let coin_type = if script_type == ScriptType::Evm {
+        coin.coin_type()
+    } else {
+        root.network().coin_type(coin.coin_type())
+    };
+
+    // m/purpose'/coin'/account'
+    let path = DerivationPath::new(vec![
+        ChildNumber::new(purpose, true)?,
+        ChildNumber::new(coin_type, true)?,
+        ChildNumber::new(account, true)?,
+    ]);
+    let origin = KeyOrigin::new(root.fingerprint(), path.clone());
+
+    let privkey = root
+        .derive_path(&path)?
+        .with_script_type(script_type)
+        .with_address_params(coin.address_params(root.network()));
+
+    Ok(ExtendedPubPrivKey::from(privkey).with_origin(origin))
+}
+
+/// Derives the keys of a chain of an account key,
+/// `m/purpose'/coin'/account'/change`.
+fn derive_chain(
+    account: &ExtendedPrivKey,
+    chain: Chain,
+    script_type: ScriptType,
+) -> Result<ExtendedPubPrivKey> {
+    check_depth(account.depth(), ACCOUNT_KEY_DEPTH)?;
+
+    let privkey = account
+        .derive_child(chain.child_number()?)?
+        .with_script_type(script_type);
+
+    Ok(ExtendedPubPrivKey::from(privkey))
 }
 
 #[cfg(test)]
@@ -89,6 +437,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_prepare_seed_invalid() {
+        let mut mnemonic = ["abandon"; 12];
+
+        assert!(matches!(
+            prepare_seed(&mnemonic, ""),
+            Err(WalletBipError::InvalidChecksum(_))
+        ));
+
+        mnemonic[3] = "abandn";
+
+        assert!(matches!(
+            prepare_seed(&mnemonic, ""),
+            Err(WalletBipError::UnknownWord { index: 3, word }) if word == "abandn"
+        ));
+    }
+
     #[test]
     fn test_prepare_root_key() {
         let mnemonic =
@@ -98,11 +463,27 @@ mod tests {
 
         let seed = prepare_seed(&mnemonic, "").unwrap();
 
-        let root = prepare_root(seed).unwrap();
+        let root = prepare_root(seed, Network::Bitcoin).unwrap();
 
         assert_eq!(
             root,
             "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz"
         );
     }
+
+    #[test]
+    fn test_prepare_root_key_testnet() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+            .split(' ')
+            .collect::<Vec<_>>();
+
+        let seed = prepare_seed(&mnemonic, "").unwrap();
+
+        let root = prepare_root(seed, Network::Regtest).unwrap();
+
+        assert_eq!(
+            root,
+            "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd"
+        );
+    }
 }
@@ -0,0 +1,534 @@
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::address;
+use crate::hd_wallet::{
+    Chain, Coin, DerivationPath, ExtendedKeyType, ExtendedPubPrivKey, Network, ScriptType, derive,
+    detect_key_type,
+};
+use crate::hex;
+use bip32::{ChildNumber, XPub};
+use core::fmt;
+
+/// The maximum number of cosigners of a legacy P2SH multisig, its redeem
+/// script must fit in 520 bytes.
+const MAX_P2SH_COSIGNERS: usize = 15;
+
+/// The maximum number of cosigners of a segwit multisig, the limit of
+/// `OP_CHECKMULTISIG` since witness scripts may be up to 3600 bytes.
+const MAX_SEGWIT_COSIGNERS: usize = 20;
+
+/// The output script a multisig address pays to.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum MultisigScriptType {
+    /// Legacy pay to script hash, `3…` (BIP45).
+    P2sh,
+
+    /// Nested segwit pay to witness script hash wrapped in pay to script
+    /// hash, `3…` (BIP48 script type 1').
+    P2shP2wsh,
+
+    /// Native segwit pay to witness script hash, `bc1q…` (BIP48 script type
+    /// 2').
+    P2wsh,
+}
+
+impl MultisigScriptType {
+    /// The maximum number of cosigners of a wallet paying to this script.
+    #[must_use]
+    pub fn max_cosigners(self) -> usize {
+        match self {
+            MultisigScriptType::P2sh => MAX_P2SH_COSIGNERS,
+            MultisigScriptType::P2shP2wsh | MultisigScriptType::P2wsh => MAX_SEGWIT_COSIGNERS,
+        }
+    }
+
+    /// The script type level of BIP48 derivation paths.
+    fn bip48_index(self) -> Result<u32> {
+        match self {
+            MultisigScriptType::P2sh => Err(WalletBipError::InvalidMultisig(
+                "BIP48 has no legacy P2SH script type".to_string(),
+            )),
+            MultisigScriptType::P2shP2wsh => Ok(1),
+            MultisigScriptType::P2wsh => Ok(2),
+        }
+    }
+}
+
+/// A `threshold` of `cosigners` multisig wallet, public keys are sorted as
+/// BIP67 requires.
+pub struct Multisig {
+    threshold: usize,
+    cosigners: Vec<XPub>,
+    script_type: MultisigScriptType,
+    network: Network,
+}
+
+/// A multisig address with the script it commits to, the redeem script of
+/// P2SH addresses and the witness script of segwit ones, hex encoded.
+pub struct MultisigAddress {
+    pub(crate) hash: String,
+    pub(crate) script: String,
+}
+
+impl MultisigAddress {
+    #[must_use]
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    #[must_use]
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+}
+
+impl fmt::Display for MultisigAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.hash)
+    }
+}
+
+impl Multisig {
+    /// Cosigner keys are `xpub`/`tpub` or the SLIP-132 multisig version of the
+    /// script type, e.g. `Zpub` for P2WSH as coordinators exchange them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cosigner key cannot be parsed, does not belong to
+    /// the network or has the version of another script type, or the
+    /// threshold does not fit the number of cosigners.
+    pub fn new(
+        threshold: usize,
+        cosigners: &[&str],
+        script_type: MultisigScriptType,
+        network: Network,
+    ) -> Result<Self> {
+        if threshold == 0
+            || threshold > cosigners.len()
+            || cosigners.len() > script_type.max_cosigners()
+        {
+            return Err(WalletBipError::InvalidMultisig(format!(
+                "{threshold} of {} is out of range",
+                cosigners.len()
+            )));
+        }
+
+        let cosigners = cosigners
+            .iter()
+            .map(|cosigner| {
+                let key_type = detect_key_type(cosigner)?;
+
+                if key_type != ExtendedKeyType::P2pkh
+                    && key_type != ExtendedKeyType::from(script_type)
+                {
+                    return Err(WalletBipError::InvalidMultisig(format!(
+                        "{key_type:?} cosigner key does not fit a {script_type:?} wallet"
+                    )));
+                }
+
+                network.parse_xpub(cosigner)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            threshold,
+            cosigners,
+            script_type,
+            network,
+        })
+    }
+
+    /// Derives the address at the given path suffix from every cosigner key,
+    /// e.g. `change/index` for BIP48 account keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the suffix has a hardened step or the derivation
+    /// fails.
+    pub fn prepare_address(&self, suffix: &[ChildNumber]) -> Result<MultisigAddress> {
+        if suffix.iter().any(ChildNumber::is_hardened) {
+            return Err(WalletBipError::HardenedFromPublic(
+                "multisig addresses are derived from cosigner public keys".to_string(),
+            ));
+        }
+
+        let pubkeys = self
+            .cosigners
+            .iter()
+            .map(|cosigner| {
+                suffix
+                    .iter()
+                    .try_fold(cosigner.clone(), |key, child| key.derive_child(*child))
+                    .map(|pubkey| pubkey.to_bytes())
+            })
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+
+        let script = sorted_script(self.threshold, pubkeys)?;
+
+        let hash = match self.script_type {
+            MultisigScriptType::P2sh => address::p2sh(&script, self.network.address_params()),
+            MultisigScriptType::P2shP2wsh => {
+                address::p2sh_p2wsh(&script, self.network.address_params())
+            }
+            MultisigScriptType::P2wsh => address::p2wsh(&script, self.network.address_params())?,
+        };
+
+        Ok(MultisigAddress {
+            hash,
+            script: hex::encode(&script, false)?,
+        })
+    }
+
+    /// Derives the address of a BIP45 wallet, cosigner keys are the purpose
+    /// keys `m/45'` and addresses are at `cosigner_index/change/index`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the derivation fails.
+    pub fn prepare_bip45_address(
+        &self,
+        cosigner_index: u32,
+        chain: Chain,
+        index: u32,
+    ) -> Result<MultisigAddress> {
+        self.prepare_address(&[
+            ChildNumber::new(cosigner_index, false)?,
+            chain.child_number()?,
+            ChildNumber::new(index, false)?,
+        ])
+    }
+
+    /// Derives the address of a BIP48 wallet, cosigner keys are the account
+    /// keys `m/48'/coin'/account'/script_type'` and addresses are at
+    /// `change/index`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the derivation fails.
+    pub fn prepare_bip48_address(&self, chain: Chain, index: u32) -> Result<MultisigAddress> {
+        self.prepare_address(&[chain.child_number()?, ChildNumber::new(index, false)?])
+    }
+}
+
+/// Builds the cosigner key of a BIP45 wallet, `m/45'`.
+///
+/// # Errors
+///
+/// Returns an error if the root key cannot be parsed, does not belong to the
+/// network or is not a root key.
+pub fn prepare_bip45_extended_key(root_key: &str, network: Network) -> Result<ExtendedPubPrivKey> {
+    derive(
+        root_key,
+        &DerivationPath::new(vec![ChildNumber::new(45, true)?]),
+        network,
+    )
+}
+
+/// Builds the cosigner account key of a BIP48 wallet,
+/// `m/48'/coin'/account'/script_type'`, serialized with the SLIP-132 multisig
+/// version of the script type (`Zpub`, `Ypub`).
+///
+/// # Errors
+///
+/// Returns an error if the coin does not use the Bitcoin address parameters,
+/// the root key cannot be parsed, does not belong to the network, is not a
+/// root key or the script type has no BIP48 index.
+pub fn prepare_bip48_account_extended_key(
+    root_key: &str,
+    coin: &Coin,
+    account: u32,
+    script_type: MultisigScriptType,
+    network: Network,
+) -> Result<ExtendedPubPrivKey> {
+    // Multisig addresses are encoded with the Bitcoin address parameters.
+    coin.check_script_type(ScriptType::P2wpkh)?;
+
+    if coin.address_params(network) != network.address_params() {
+        return Err(WalletBipError::InvalidCoin(format!(
+            "{} multisig addresses cannot be encoded",
+            coin.symbol()
+        )));
+    }
+
+    let path = DerivationPath::new(vec![
+        ChildNumber::new(48, true)?,
+        ChildNumber::new(network.coin_type(coin.coin_type()), true)?,
+        ChildNumber::new(account, true)?,
+        ChildNumber::new(script_type.bip48_index()?, true)?,
+    ]);
+
+    Ok(derive(root_key, &path, network)?.convert(script_type.into()))
+}
+
+/// Builds the multisig script with the public keys sorted lexicographically
+/// (BIP67).
+fn sorted_script(threshold: usize, mut pubkeys: Vec<[u8; 33]>) -> Result<Vec<u8>> {
+    pubkeys.sort_unstable();
+
+    address::multisig_script(threshold, &pubkeys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::AddressParams;
+    use rstest::rstest;
+
+    const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    const COSIGNERS: [&str; 3] = [
+        "xpub6DNacWSbNjuHdUKQ9n7cutFhieeSTsvAhgTvmZUm79jqGRbCFgmedGKExRZHEfSzzCdxRTkXTGr34oRWruVGS8ie12imQJ4dhVCQAHWFstY",
+        "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj",
+        "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V",
+    ];
+
+    #[test]
+    fn test_sorted_script() {
+        let pubkeys = [
+            "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8",
+            "02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f",
+        ]
+        .iter()
+        .map(|pubkey| hex::decode(pubkey).unwrap().try_into().unwrap())
+        .collect();
+
+        let script = sorted_script(2, pubkeys).unwrap();
+
+        assert_eq!(
+            hex::encode(&script, false).unwrap(),
+            "522102fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f2102ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f852ae"
+        );
+        assert_eq!(
+            address::p2sh(&script, AddressParams::BITCOIN),
+            "39bgKC7RFbpoCRbtD5KEdkYKtNyhpsNa3Z"
+        );
+    }
+
+    #[rstest]
+    #[case(
+        MultisigScriptType::P2sh,
+        ["3AFhPe68ShjFsMzgd4tQxvgrfe3TTY2zv7", "3QLovKcyrFUThTqqUSvaMZhLARtViT2MZw"]
+    )]
+    #[case(
+        MultisigScriptType::P2shP2wsh,
+        ["3FBoM6DVMLsWjfwLZRhXWWxfx8vMcPpuTb", "3CggsMnvSr3bC25sQhHsqPbuUjG81A4UoA"]
+    )]
+    #[case(
+        MultisigScriptType::P2wsh,
+        [
+            "bc1q4wts68qu0gpaplew3j5tk9ywpa260qlkp0fnt99t5fxpf5gv26csx6un95",
+            "bc1qsannh3v72nsrjxaukxnwevx4w39hd7z9z7z2xrjyzgvefgjw75wszpsvhr"
+        ]
+    )]
+    fn test_prepare_addresses(#[case] script_type: MultisigScriptType, #[case] hashes: [&str; 2]) {
+        let multisig = Multisig::new(2, &COSIGNERS, script_type, Network::Bitcoin).unwrap();
+
+        for (index, hash) in (0..).zip(hashes) {
+            let result = multisig
+                .prepare_bip48_address(Chain::External, index)
+                .unwrap();
+
+            assert_eq!(result.hash, hash);
+        }
+    }
+
+    #[test]
+    fn test_cosigner_order() {
+        let mut reversed = COSIGNERS;
+        reversed.reverse();
+
+        let multisig = Multisig::new(2, &COSIGNERS, MultisigScriptType::P2wsh, Network::Bitcoin)
+            .unwrap()
+            .prepare_bip48_address(Chain::Internal, 3)
+            .unwrap();
+        let reversed = Multisig::new(2, &reversed, MultisigScriptType::P2wsh, Network::Bitcoin)
+            .unwrap()
+            .prepare_bip48_address(Chain::Internal, 3)
+            .unwrap();
+
+        assert_eq!(multisig.hash, reversed.hash);
+        assert_eq!(multisig.script, reversed.script);
+    }
+
+    #[test]
+    fn test_prepare_bip45_extended_key() {
+        let extended = prepare_bip45_extended_key(ROOT, Network::Bitcoin).unwrap();
+
+        assert_eq!(
+            extended.pubkey.to_string(),
+            "xpub68jrRzQopSUSiczuqjRwvVn3CFtSEZY6a3jbT66LM3tvt1rXtYT7Udi8dt3m1qj3q8pKZjt7tqrSt7bRN4LD2vSVq1167PSA5AyM31FUHwU"
+        );
+
+        let multisig = Multisig::new(
+            1,
+            &[extended.pubkey.to_string().as_str()],
+            MultisigScriptType::P2sh,
+            Network::Bitcoin,
+        )
+        .unwrap();
+
+        assert_eq!(
+            multisig
+                .prepare_bip45_address(0, Chain::External, 0)
+                .unwrap()
+                .hash,
+            multisig
+                .prepare_address(&[
+                    ChildNumber::new(0, false).unwrap(),
+                    ChildNumber::new(0, false).unwrap(),
+                    ChildNumber::new(0, false).unwrap(),
+                ])
+                .unwrap()
+                .hash
+        );
+    }
+
+    #[rstest]
+    #[case(
+        MultisigScriptType::P2shP2wsh,
+        "Ypub6jUbbRukkGPp4DgJDD4HL2NKkSZ1UPk111mg59XtJRQZHvJ6XqvJzrntik9U4jCFQkgrBqevdKLPMdYZXU9KAGhKpMhW5XujwqiQ7Csmm4Z"
+    )]
+    #[case(
+        MultisigScriptType::P2wsh,
+        "Zpub74Jru6aftwwHxCUCWEvP6DgrfFsdA4U6ZRtQ5i8qJpMcC39yZGv3egBhQfV3MS9pZtH5z8iV5qWkJsK6ESs6mSzt4qvGhzJxPeeVS2e1zUG"
+    )]
+    fn test_prepare_bip48_account_extended_key(
+        #[case] script_type: MultisigScriptType,
+        #[case] pubkey: &str,
+    ) {
+        let extended =
+            prepare_bip48_account_extended_key(ROOT, &Coin::BTC, 0, script_type, Network::Bitcoin)
+                .unwrap();
+
+        assert_eq!(extended.pubkey.to_string(), pubkey);
+    }
+
+    #[test]
+    fn test_slip132_cosigners() {
+        let cosigners = (0..3)
+            .map(|account| {
+                prepare_bip48_account_extended_key(
+                    ROOT,
+                    &Coin::BTC,
+                    account,
+                    MultisigScriptType::P2wsh,
+                    Network::Bitcoin,
+                )
+                .unwrap()
+                .pubkey
+                .to_string()
+            })
+            .collect::<Vec<_>>();
+        let zpubs = cosigners.iter().map(String::as_str).collect::<Vec<_>>();
+        let xpubs = cosigners
+            .iter()
+            .map(|zpub| {
+                crate::hd_wallet::convert_key(zpub, ExtendedKeyType::P2pkh, Network::Bitcoin)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let xpubs = xpubs.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let address = |cosigners: &[&str]| {
+            Multisig::new(2, cosigners, MultisigScriptType::P2wsh, Network::Bitcoin)
+                .unwrap()
+                .prepare_bip48_address(Chain::External, 0)
+                .unwrap()
+                .hash
+        };
+
+        assert!(zpubs.iter().all(|zpub| zpub.starts_with("Zpub")));
+        assert_eq!(address(&zpubs), address(&xpubs));
+        assert!(matches!(
+            Multisig::new(2, &zpubs, MultisigScriptType::P2shP2wsh, Network::Bitcoin),
+            Err(WalletBipError::InvalidMultisig(_))
+        ));
+    }
+
+    #[rstest]
+    #[case(MultisigScriptType::P2sh, 15)]
+    #[case(MultisigScriptType::P2shP2wsh, 20)]
+    #[case(MultisigScriptType::P2wsh, 20)]
+    fn test_max_cosigners(#[case] script_type: MultisigScriptType, #[case] max: usize) {
+        let cosigners = (0..=u32::try_from(max).unwrap())
+            .map(|index| {
+                derive(
+                    ROOT,
+                    &DerivationPath::new(vec![ChildNumber::new(index, true).unwrap()]),
+                    Network::Bitcoin,
+                )
+                .unwrap()
+                .pubkey
+                .to_string()
+            })
+            .collect::<Vec<_>>();
+        let cosigners = cosigners.iter().map(String::as_str).collect::<Vec<_>>();
+
+        assert_eq!(script_type.max_cosigners(), max);
+
+        let multisig =
+            Multisig::new(max, &cosigners[..max], script_type, Network::Bitcoin).unwrap();
+        assert!(multisig.prepare_bip48_address(Chain::External, 0).is_ok());
+
+        assert!(Multisig::new(max, &cosigners, script_type, Network::Bitcoin).is_err());
+    }
+
+    #[rstest]
+    #[case(0, 3)]
+    #[case(4, 3)]
+    fn test_invalid_threshold(#[case] threshold: usize, #[case] count: usize) {
+        assert!(
+            Multisig::new(
+                threshold,
+                &COSIGNERS[..count],
+                MultisigScriptType::P2wsh,
+                Network::Bitcoin
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_hardened_suffix() {
+        let multisig =
+            Multisig::new(2, &COSIGNERS, MultisigScriptType::P2wsh, Network::Bitcoin).unwrap();
+
+        assert!(matches!(
+            multisig.prepare_address(&[ChildNumber::new(0, true).unwrap()]),
+            Err(WalletBipError::HardenedFromPublic(_))
+        ));
+    }
+
+    #[test]
+    fn test_bip48_legacy_script_type() {
+        assert!(
+            prepare_bip48_account_extended_key(
+                ROOT,
+                &Coin::BTC,
+                0,
+                MultisigScriptType::P2sh,
+                Network::Bitcoin
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_bip48_other_coin() {
+        let ltc = crate::hd_wallet::CoinRegistry::default()
+            .by_symbol("LTC")
+            .unwrap()
+            .clone();
+
+        assert!(matches!(
+            prepare_bip48_account_extended_key(
+                ROOT,
+                &ltc,
+                0,
+                MultisigScriptType::P2wsh,
+                Network::Bitcoin
+            ),
+            Err(WalletBipError::InvalidCoin(_))
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::slip132;
+use crate::hd_wallet::{AddressParams, ScriptType};
+use bip32::{ExtendedKey, Prefix, XPrv, XPub};
+use core::fmt;
+use std::str::FromStr;
+
+/// The coin type shared by all test networks (SLIP-44).
+const TESTNET_COIN_TYPE: u32 = 1;
+
+/// The Bitcoin network keys and addresses are serialized for.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum Network {
+    #[default]
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    #[must_use]
+    pub fn is_mainnet(self) -> bool {
+        self == Network::Bitcoin
+    }
+
+    /// Coin type used in BIP44-like derivation paths, every test network uses
+    /// coin type 1 whatever the coin is.
+    #[must_use]
+    pub fn coin_type(self, coin_type: u32) -> u32 {
+        if self.is_mainnet() {
+            coin_type
+        } else {
+            TESTNET_COIN_TYPE
+        }
+    }
+
+    /// SLIP-132 prefixes of the extended public and private keys.
+    #[must_use]
+    pub fn prefixes(self, script_type: ScriptType) -> (Prefix, Prefix) {
+        slip132::prefixes(script_type.into(), self.is_mainnet())
+    }
+
+    /// The Bitcoin address parameters of the network, the ones of other coins
+    /// come with their [`Coin`](crate::hd_wallet::Coin).
+    #[must_use]
+    pub fn address_params(self) -> AddressParams {
+        match self {
+            Network::Bitcoin => AddressParams::BITCOIN,
+            Network::Testnet | Network::Signet => AddressParams::BITCOIN_TESTNET,
+            Network::Regtest => AddressParams::BITCOIN_REGTEST,
+        }
+    }
+
+    /// Parses an extended private key and checks that its version bytes belong
+    /// to the network.
+    pub(crate) fn parse_xprv(self, key: &str) -> Result<XPrv> {
+        Ok(XPrv::try_from(self.parse_extended_key(key)?)?)
+    }
+
+    /// Parses an extended public key and checks that its version bytes belong
+    /// to the network.
+    pub(crate) fn parse_xpub(self, key: &str) -> Result<XPub> {
+        Ok(XPub::try_from(self.parse_extended_key(key)?)?)
+    }
+
+    pub(crate) fn parse_extended_key(self, key: &str) -> Result<ExtendedKey> {
+        let extended = ExtendedKey::from_str(key)?;
+
+        self.check_version(extended.prefix)?;
+
+        Ok(extended)
+    }
+
+    /// Checks that a key version belongs to the network.
+    pub(crate) fn check_version(self, prefix: Prefix) -> Result<()> {
+        let actual = slip132::version_network(prefix)?;
+
+        if actual.is_mainnet() != self.is_mainnet() {
+            return Err(WalletBipError::NetworkMismatch {
+                expected: self,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Network::Bitcoin => "bitcoin",
+            Network::Testnet => "testnet",
+            Network::Signet => "signet",
+            Network::Regtest => "regtest",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const XPRV: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    const TPRV: &str = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd";
+
+    #[rstest]
+    #[case(Network::Bitcoin, XPRV)]
+    #[case(Network::Testnet, TPRV)]
+    #[case(Network::Signet, TPRV)]
+    #[case(Network::Regtest, TPRV)]
+    fn test_parse_xprv(#[case] network: Network, #[case] key: &str) {
+        assert!(network.parse_xprv(key).is_ok());
+    }
+
+    #[rstest]
+    #[case(Network::Bitcoin, TPRV, Network::Testnet)]
+    #[case(Network::Testnet, XPRV, Network::Bitcoin)]
+    #[case(Network::Regtest, XPRV, Network::Bitcoin)]
+    fn test_parse_xprv_network_mismatch(
+        #[case] network: Network,
+        #[case] key: &str,
+        #[case] actual: Network,
+    ) {
+        assert!(matches!(
+            network.parse_xprv(key),
+            Err(WalletBipError::NetworkMismatch { expected, actual: found })
+                if expected == network && found == actual
+        ));
+    }
+
+    #[test]
+    fn test_parse_xpub() {
+        let tpub = "tpubDC5FSnBiZDMmhiuCmWAYsLwgLYrrT9rAqvTySfuCCrgsWz8wxMXUS9Tb9iVMvcRbvFcAHGkMD5Kx8koh4GquNGNTfohfk7pgjhaPCdXpoba";
+
+        assert!(Network::Testnet.parse_xpub(tpub).is_ok());
+        assert!(Network::Bitcoin.parse_xpub(tpub).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Checks that a supplied account or chain key belongs to the expected
+//! wallet, so a key of another wallet with the right depth is not accepted.
+
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::{
+    DerivationPath, ExtendedPrivKey, ExtendedPubKey, KeyOrigin, ROOT_KEY_DEPTH, check_depth,
+};
+
+/// What [`ExtendedPubKey::verify_origin`] could establish about a key.
+#[must_use]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OriginVerification {
+    /// The key is the master key or a direct child of the master key of the
+    /// origin.
+    Verified,
+
+    /// The depth and the child number match, but the key only records the
+    /// fingerprint of its parent and may belong to another wallet. Only
+    /// [`ExtendedPubKey::verify_derived_from`] can verify it.
+    Unverifiable,
+}
+
+impl ExtendedPubKey {
+    /// Checks the key against its expected origin with what the key records:
+    /// its depth, its child number and, for the master key and its direct
+    /// children, the master fingerprint.
+    ///
+    /// Deeper keys only record the fingerprint of their parent and are
+    /// reported [`OriginVerification::Unverifiable`], see
+    /// [`ExtendedPubKey::verify_derived_from`] when the master key is
+    /// available.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key does not match the origin.
+    pub fn verify_origin(&self, origin: &KeyOrigin) -> Result<OriginVerification> {
+        let path = origin.path();
+
+        if usize::from(self.depth()) != path.len() {
+            return Err(WalletBipError::OriginMismatch(format!(
+                "key depth is {}, {origin} has {} levels",
+                self.depth(),
+                path.len()
+            )));
+        }
+
+        let Some(&child_number) = path.as_ref().last() else {
+            if self.fingerprint() != origin.fingerprint() {
+                return Err(WalletBipError::OriginMismatch(format!(
+                    "key is not the master key of {origin}"
+                )));
+            }
+
+            return Ok(OriginVerification::Verified);
+        };
+
+        if self.child_number() != child_number {
+            return Err(WalletBipError::OriginMismatch(format!(
+                "key is child {}, {origin} ends with {child_number}",
+                self.child_number()
+            )));
+        }
+
+        if path.len() > 1 {
+            return Ok(OriginVerification::Unverifiable);
+        }
+
+        if self.parent_fingerprint() != origin.fingerprint() {
+            return Err(WalletBipError::OriginMismatch(format!(
+                "key is not a child of the master key of {origin}"
+            )));
+        }
+
+        Ok(OriginVerification::Verified)
+    }
+
+    /// Checks that the key is the key at a path of a master key by deriving
+    /// it again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root is not a master key, the keys belong to
+    /// different networks, the derivation fails or the key does not match.
+    pub fn verify_derived_from(&self, root: &ExtendedPrivKey, path: &DerivationPath) -> Result<()> {
+        check_depth(root.depth(), ROOT_KEY_DEPTH)?;
+
+        if self.network().is_mainnet() != root.network().is_mainnet() {
+            return Err(WalletBipError::NetworkMismatch {
+                expected: self.network(),
+                actual: root.network(),
+            });
+        }
+
+        let origin = KeyOrigin::new(root.fingerprint(), path.clone());
+
+        // rejects keys at another depth or child number before deriving, the
+        // derivation verifies the rest
+        let _ = self.verify_origin(&origin)?;
+
+        if root.derive_path(path)?.public_key().as_xpub() != self.as_xpub() {
+            return Err(WalletBipError::OriginMismatch(format!(
+                "key is not the key at {origin}"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl ExtendedPrivKey {
+    /// See [`ExtendedPubKey::verify_origin`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key does not match the origin.
+    pub fn verify_origin(&self, origin: &KeyOrigin) -> Result<OriginVerification> {
+        self.public_key().verify_origin(origin)
+    }
+
+    /// See [`ExtendedPubKey::verify_derived_from`], the public key determines
+    /// the private key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root is not a master key, the keys belong to
+    /// different networks, the derivation fails or the key does not match.
+    pub fn verify_derived_from(&self, root: &ExtendedPrivKey, path: &DerivationPath) -> Result<()> {
+        self.public_key().verify_derived_from(root, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::Network;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    const OTHER_ROOT: &str = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
+
+    const ACCOUNT: &str = "zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE";
+
+    fn path(path: &str) -> DerivationPath {
+        DerivationPath::from_str(path).unwrap()
+    }
+
+    fn key_at(root: &str, at: &str) -> ExtendedPrivKey {
+        ExtendedPrivKey::parse(root, Network::Bitcoin)
+            .unwrap()
+            .derive_path(&path(at))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_verify_derived_from() {
+        let root = ExtendedPrivKey::parse(ROOT, Network::Bitcoin).unwrap();
+        let account = ExtendedPrivKey::parse(ACCOUNT, Network::Bitcoin).unwrap();
+
+        account
+            .verify_derived_from(&root, &path("m/84'/0'/0'"))
+            .unwrap();
+        account
+            .public_key()
+            .verify_derived_from(&root, &path("m/84'/0'/0'"))
+            .unwrap();
+        key_at(ROOT, "m/84'/0'/0'/1")
+            .verify_derived_from(&root, &path("m/84'/0'/0'/1"))
+            .unwrap();
+    }
+
+    #[rstest]
+    // another account of the same wallet
+    #[case(ROOT, "m/84'/0'/1'", "m/84'/0'/0'")]
+    // the same account of another wallet
+    #[case(OTHER_ROOT, "m/84'/0'/0'", "m/84'/0'/0'")]
+    // the same child numbers under another purpose
+    #[case(ROOT, "m/44'/0'/0'", "m/84'/0'/0'")]
+    // the change chain instead of the receive chain
+    #[case(ROOT, "m/84'/0'/0'/1", "m/84'/0'/0'/0")]
+    fn test_verify_derived_from_mismatch(
+        #[case] root: &str,
+        #[case] derived_at: &str,
+        #[case] expected_at: &str,
+    ) {
+        let key = key_at(root, derived_at);
+
+        assert!(matches!(
+            key.verify_derived_from(
+                &ExtendedPrivKey::parse(ROOT, Network::Bitcoin).unwrap(),
+                &path(expected_at)
+            ),
+            Err(WalletBipError::OriginMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_derived_from_not_root() {
+        let account = ExtendedPrivKey::parse(ACCOUNT, Network::Bitcoin).unwrap();
+
+        assert!(matches!(
+            account.verify_derived_from(&account, &path("m/0")),
+            Err(WalletBipError::InvalidDepth {
+                expected: 0,
+                actual: 3
+            })
+        ));
+    }
+
+    #[rstest]
+    #[case(ROOT, "m", "m", Some(OriginVerification::Verified))]
+    #[case(OTHER_ROOT, "m", "m", None)]
+    #[case(ROOT, "m/0'", "m/0'", Some(OriginVerification::Verified))]
+    #[case(OTHER_ROOT, "m/0'", "m/0'", None)]
+    #[case(ROOT, "m/0'", "m/0", None)]
+    #[case(ROOT, "m/0'", "m/0'/0", None)]
+    #[case(ROOT, "m/84'/0'/1'", "m/84'/0'/0'", None)]
+    // deeper keys only record the fingerprint of their parent
+    #[case(
+        ROOT,
+        "m/84'/0'/0'",
+        "m/84'/0'/0'",
+        Some(OriginVerification::Unverifiable)
+    )]
+    #[case(
+        OTHER_ROOT,
+        "m/84'/0'/0'",
+        "m/84'/0'/0'",
+        Some(OriginVerification::Unverifiable)
+    )]
+    fn test_verify_origin(
+        #[case] root: &str,
+        #[case] derived_at: &str,
+        #[case] expected_at: &str,
+        #[case] expected: Option<OriginVerification>,
+    ) {
+        let origin = KeyOrigin::new([0x73, 0xc5, 0xda, 0x0a], path(expected_at));
+
+        assert_eq!(
+            key_at(root, derived_at).verify_origin(&origin).ok(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::{
+    ExtendedPrivKey, ExtendedPubPrivKey, KeyOrigin, Network, ROOT_KEY_DEPTH, ScriptType,
+    check_depth,
+};
+use bip32::{ChildNumber, XPrv};
+use core::fmt;
+use std::str::FromStr;
+
+/// A BIP32 derivation path starting at the master key, e.g. `m/44'/0'/0'`.
+///
+/// Hardened indices may be marked with `'`, `h` or `H`, the path is always
+/// displayed with `'`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl DerivationPath {
+    #[must_use]
+    pub fn new(children: Vec<ChildNumber>) -> Self {
+        Self(children)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ChildNumber> + '_ {
+        self.0.iter().copied()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the path extended with one more child.
+    #[must_use]
+    pub fn child(&self, child_number: ChildNumber) -> Self {
+        let mut children = self.0.clone();
+        children.push(child_number);
+
+        Self(children)
+    }
+}
+
+impl AsRef<[ChildNumber]> for DerivationPath {
+    fn as_ref(&self) -> &[ChildNumber] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = WalletBipError;
+
+    fn from_str(path: &str) -> Result<Self> {
+        let mut segments = path.split('/');
+
+        if segments.next() != Some("m") {
+            return Err(WalletBipError::InvalidPath(format!(
+                "{path:?} must start with \"m\""
+            )));
+        }
+
+        segments
+            .map(|segment| parse_child(segment, path))
+            .collect::<Result<Vec<_>>>()
+            .map(Self)
+    }
+}
+
+/// Parses a single path segment such as `44'`, `0h` or `7`, `path` is only
+/// used in error messages.
+pub(crate) fn parse_child(segment: &str, path: &str) -> Result<ChildNumber> {
+    let (index, hardened) = match segment
+        .strip_suffix(['\'', 'h', 'H'])
+        .map(|index| (index, true))
+    {
+        Some(hardened) => hardened,
+        None => (segment, false),
+    };
+
+    if index.is_empty() || !index.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(WalletBipError::InvalidPath(format!(
+            "invalid index {segment:?} in {path:?}"
+        )));
+    }
+
+    index
+        .parse::<u32>()
+        .ok()
+        .and_then(|index| ChildNumber::new(index, hardened).ok())
+        .ok_or_else(|| {
+            WalletBipError::InvalidPath(format!("index {segment:?} in {path:?} is out of range"))
+        })
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+
+        for child in &self.0 {
+            write!(f, "/{child}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A chain of a BIP44-like account, the level after the account key.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Chain {
+    /// The receive addresses, chain 0.
+    External,
+
+    /// The change addresses, chain 1.
+    Internal,
+
+    /// Any other chain, for wallets keeping e.g. reserved addresses apart.
+    /// Chains 0 and 1 are [`Chain::External`] and [`Chain::Internal`], build
+    /// chains from their index with [`Chain::from`].
+    Custom(u32),
+}
+
+impl Chain {
+    #[must_use]
+    pub fn index(self) -> u32 {
+        match self {
+            Chain::External => 0,
+            Chain::Internal => 1,
+            Chain::Custom(index) => index,
+        }
+    }
+
+    /// The non-hardened child number of the chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a custom chain is out of range or is the external
+    /// or internal chain.
+    pub fn child_number(self) -> Result<ChildNumber> {
+        if let Chain::Custom(index @ (0 | 1)) = self {
+            return Err(WalletBipError::InvalidPath(format!(
+                "custom chain {index} is {:?}",
+                Chain::from(index)
+            )));
+        }
+
+        Ok(ChildNumber::new(self.index(), false)?)
+    }
+}
+
+impl From<u32> for Chain {
+    fn from(index: u32) -> Self {
+        match index {
+            0 => Chain::External,
+            1 => Chain::Internal,
+            index => Chain::Custom(index),
+        }
+    }
+}
+
+/// Derives the extended keys at the given path from a root extended private
+/// key.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be parsed, does not belong to the
+/// network, is not a root key or the derivation fails.
+pub fn derive(
+    root_key: &str,
+    path: &DerivationPath,
+    network: Network,
+) -> Result<ExtendedPubPrivKey> {
+    let root = ExtendedPrivKey::parse(root_key, network)?;
+
+    check_depth(root.depth(), ROOT_KEY_DEPTH)?;
+
+    derive_xprv(&root, path)
+}
+
+/// Derives the extended keys at the given path from a seed.
+///
+/// # Errors
+///
+/// Returns an error if the seed has an unsupported length or the derivation
+/// fails.
+pub fn derive_from_seed<S: AsRef<[u8]>>(
+    seed: S,
+    path: &DerivationPath,
+    network: Network,
+) -> Result<ExtendedPubPrivKey> {
+    derive_xprv(
+        &ExtendedPrivKey::new(XPrv::new(seed)?, ScriptType::P2pkh, network),
+        path,
+    )
+}
+
+fn derive_xprv(root: &ExtendedPrivKey, path: &DerivationPath) -> Result<ExtendedPubPrivKey> {
+    let origin = KeyOrigin::new(root.fingerprint(), path.clone());
+
+    let privkey = root.derive_path(path)?.with_script_type(ScriptType::P2pkh);
+
+    Ok(ExtendedPubPrivKey::from(privkey).with_origin(origin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::{prepare_root, prepare_seed};
+    use rstest::rstest;
+
+    const ROOT: &str = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
+
+    #[rstest]
+    #[case("m", "m")]
+    #[case("m/0'/0'", "m/0'/0'")]
+    #[case("m/44h/0H/0'/1/7", "m/44'/0'/0'/1/7")]
+    #[case("m/2147483647'/2147483647", "m/2147483647'/2147483647")]
+    fn test_parse_path(#[case] path: &str, #[case] expected: &str) {
+        let path = DerivationPath::from_str(path).unwrap();
+
+        assert_eq!(path.to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("0/1")]
+    #[case("m/")]
+    #[case("m/x")]
+    #[case("m/-1")]
+    #[case("m/1''")]
+    #[case("m/+1")]
+    #[case("m/2147483648")]
+    #[case("m/4294967296'")]
+    fn test_parse_invalid_path(#[case] path: &str) {
+        assert!(DerivationPath::from_str(path).is_err());
+    }
+
+    #[rstest]
+    #[case(
+        "m/0'/0'",
+        "xprv9wfndKaiDKD8UKCVyYhDG5boquxEqEZD2Dr2CNxNDKLtZw3tqvJZ1DgFyqWqa2DPwSoApgDy7BdrG8YaxbHTdGMWMP5X2n957iBYPAfDKKy"
+    )]
+    #[case(
+        "m/0h/0",
+        "xprv9wfndKaZsegAGojErEykgw5Td6nW1qXkbkoVRHCFV2yPv6gNkTbKNpqrLfTXoi8HVVCPLcUNtQaWFao3ecE53qeYRcE5jVBCyCAiXiVW86y"
+    )]
+    #[case(
+        "m/44H/0H/0H",
+        "xprv9zPECzuhYNLzQzEw3kacYkJyAcox4RCKLTYKyB59YpCrPdG3i9TQ5Tzm78LmpheejAPKy1JBKgDqSvouiqrirfVxVXoKhdmi5mVMEWGFr6S"
+    )]
+    #[case(
+        "m/44'/0'/0'/0",
+        "xprvA1Nd3YgbqxvrcjZsCA96KsyPrLMK4TbPQq983aiN4k9Sx3DM5aKBnY7ejaasiCcqEwSbLP7QFnDJr2qxcjKhr6fPzQUGrGTS42T5QfQK9tL"
+    )]
+    fn test_derive(#[case] path: &str, #[case] privkey: &str) {
+        let path = DerivationPath::from_str(path).unwrap();
+
+        let extended = derive(ROOT, &path, Network::Bitcoin).unwrap();
+
+        assert_eq!(extended.privkey.to_string(), privkey);
+    }
+
+    #[test]
+    fn test_derive_root() {
+        let extended = derive(ROOT, &DerivationPath::default(), Network::Bitcoin).unwrap();
+
+        assert_eq!(extended.privkey.to_string(), ROOT);
+    }
+
+    #[test]
+    fn test_derive_from_seed() {
+        let mnemonic =
+            "dragon elbow sheriff outdoor undo brisk aware raw inform correct lake truly"
+                .split(' ')
+                .collect::<Vec<_>>();
+        let seed = prepare_seed(&mnemonic, "").unwrap();
+        let path = DerivationPath::from_str("m/44'/0'/0'").unwrap();
+
+        assert_eq!(
+            derive_from_seed(seed, &path, Network::Bitcoin)
+                .unwrap()
+                .privkey
+                .to_string(),
+            derive(
+                &prepare_root(seed, Network::Bitcoin).unwrap(),
+                &path,
+                Network::Bitcoin
+            )
+            .unwrap()
+            .privkey
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_derive_not_root() {
+        let account = "xprv9zPECzuhYNLzQzEw3kacYkJyAcox4RCKLTYKyB59YpCrPdG3i9TQ5Tzm78LmpheejAPKy1JBKgDqSvouiqrirfVxVXoKhdmi5mVMEWGFr6S";
+
+        assert!(
+            derive(
+                account,
+                &DerivationPath::from_str("m/0").unwrap(),
+                Network::Bitcoin
+            )
+            .is_err()
+        );
+    }
+
+    #[rstest]
+    #[case(0, Chain::External)]
+    #[case(1, Chain::Internal)]
+    #[case(2, Chain::Custom(2))]
+    fn test_chain_from_index(#[case] index: u32, #[case] chain: Chain) {
+        assert_eq!(Chain::from(index), chain);
+        assert_eq!(chain.index(), index);
+        assert_eq!(chain.child_number().unwrap().index(), index);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    fn test_custom_chain_duplicate(#[case] index: u32) {
+        assert_ne!(Chain::Custom(index), Chain::from(index));
+        assert!(matches!(
+            Chain::Custom(index).child_number(),
+            Err(WalletBipError::InvalidPath(_))
+        ));
+    }
+}
@@ -0,0 +1,595 @@
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::{
+    Address, Addresses, Chain, Coin, DerivationPath, ElectrumSeedType, ExtendedPrivKey, Network,
+    ROOT_KEY_DEPTH, ScriptType, check_depth,
+};
+use bip32::ChildNumber;
+use core::fmt;
+use core::ops::Range;
+
+/// A level of the derivation path template of a wallet.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum PathStep {
+    /// An index every address shares, e.g. the purpose of BIP44-like paths.
+    Fixed { index: u32, hardened: bool },
+
+    /// The SLIP-44 coin type, hardened.
+    Coin,
+
+    /// The account number, hardened.
+    Account,
+
+    /// 0 for the receive chain, 1 for the change chain.
+    Change { hardened: bool },
+
+    /// The address index.
+    Address { hardened: bool },
+}
+
+/// How a wallet software turns its mnemonic into the master key.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum SeedScheme {
+    /// A BIP39 mnemonic, see [`prepare_seed`](crate::hd_wallet::prepare_seed).
+    Bip39,
+
+    /// An Electrum seed of the given type, see
+    /// [`prepare_electrum_seed`](crate::hd_wallet::prepare_electrum_seed).
+    Electrum(ElectrumSeedType),
+}
+
+/// The derivation layout of a wallet software: which seed its master key is
+/// built from, where its addresses live and which script they pay to.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct WalletPreset {
+    name: &'static str,
+    seed: SeedScheme,
+    steps: &'static [PathStep],
+    script_type: ScriptType,
+}
+
+const fn fixed(index: u32) -> PathStep {
+    PathStep::Fixed {
+        index,
+        hardened: false,
+    }
+}
+
+const fn hardened(index: u32) -> PathStep {
+    PathStep::Fixed {
+        index,
+        hardened: true,
+    }
+}
+
+const CHANGE: PathStep = PathStep::Change { hardened: false };
+
+const ADDRESS: PathStep = PathStep::Address { hardened: false };
+
+/// `m/44'/coin'/account'/change/index`.
+const BIP44_STEPS: &[PathStep] = &[
+    hardened(44),
+    PathStep::Coin,
+    PathStep::Account,
+    CHANGE,
+    ADDRESS,
+];
+
+/// `m/84'/coin'/account'/change/index`.
+const BIP84_STEPS: &[PathStep] = &[
+    hardened(84),
+    PathStep::Coin,
+    PathStep::Account,
+    CHANGE,
+    ADDRESS,
+];
+
+impl WalletPreset {
+    /// Bitcoin Core HD wallets before descriptors (0.13 to 0.20),
+    /// `m/0'/change'/index'`.
+    pub const BITCOIN_CORE: WalletPreset = WalletPreset {
+        name: "Bitcoin Core",
+        seed: SeedScheme::Bip39,
+        steps: &[
+            hardened(0),
+            PathStep::Change { hardened: true },
+            PathStep::Address { hardened: true },
+        ],
+        script_type: ScriptType::P2pkh,
+    };
+
+    /// Multibit HD, `m/0'/change/index`.
+    pub const MULTIBIT_HD: WalletPreset = WalletPreset {
+        name: "Multibit HD",
+        seed: SeedScheme::Bip39,
+        steps: &[hardened(0), CHANGE, ADDRESS],
+        script_type: ScriptType::P2pkh,
+    };
+
+    /// Electrum standard wallets, `m/change/index` from the master key of an
+    /// Electrum seed. A BIP39 phrase imported into Electrum follows the BIP44
+    /// or BIP84 layout it was imported with instead.
+    pub const ELECTRUM: WalletPreset = WalletPreset {
+        name: "Electrum",
+        seed: SeedScheme::Electrum(ElectrumSeedType::Standard),
+        steps: &[CHANGE, ADDRESS],
+        script_type: ScriptType::P2pkh,
+    };
+
+    /// Electrum native segwit wallets, `m/0'/change/index` from the master
+    /// key of an Electrum segwit seed.
+    pub const ELECTRUM_SEGWIT: WalletPreset = WalletPreset {
+        name: "Electrum segwit",
+        seed: SeedScheme::Electrum(ElectrumSeedType::Segwit),
+        steps: &[hardened(0), CHANGE, ADDRESS],
+        script_type: ScriptType::P2wpkh,
+    };
+
+    /// Ledger Live Bitcoin accounts, native segwit by default. The BIP84
+    /// layout, shared with [`WalletPreset::TREZOR`] and
+    /// [`WalletPreset::SAMOURAI`].
+    pub const LEDGER_LIVE: WalletPreset = WalletPreset {
+        name: "Ledger Live",
+        seed: SeedScheme::Bip39,
+        steps: BIP84_STEPS,
+        script_type: ScriptType::P2wpkh,
+    };
+
+    /// Ledger Chrome app and early `MyEtherWallet` Ethereum accounts,
+    /// `m/44'/coin'/0'/index` without a change level.
+    pub const LEDGER_LEGACY_ETHEREUM: WalletPreset = WalletPreset {
+        name: "Ledger legacy Ethereum",
+        seed: SeedScheme::Bip39,
+        steps: &[hardened(44), PathStep::Coin, hardened(0), ADDRESS],
+        script_type: ScriptType::Evm,
+    };
+
+    /// Trezor Suite Bitcoin accounts, the BIP84 layout of
+    /// [`WalletPreset::LEDGER_LIVE`].
+    pub const TREZOR: WalletPreset = WalletPreset {
+        name: "Trezor",
+        seed: SeedScheme::Bip39,
+        steps: BIP84_STEPS,
+        script_type: ScriptType::P2wpkh,
+    };
+
+    /// Mycelium HD accounts, the legacy BIP44 layout shared with
+    /// [`WalletPreset::COINOMI`].
+    pub const MYCELIUM: WalletPreset = WalletPreset {
+        name: "Mycelium",
+        seed: SeedScheme::Bip39,
+        steps: BIP44_STEPS,
+        script_type: ScriptType::P2pkh,
+    };
+
+    /// Coinomi, the BIP44 layout of [`WalletPreset::MYCELIUM`] for every coin.
+    pub const COINOMI: WalletPreset = WalletPreset {
+        name: "Coinomi",
+        seed: SeedScheme::Bip39,
+        steps: BIP44_STEPS,
+        script_type: ScriptType::P2pkh,
+    };
+
+    /// Exodus Bitcoin wallets, a single native segwit account.
+    pub const EXODUS: WalletPreset = WalletPreset {
+        name: "Exodus",
+        seed: SeedScheme::Bip39,
+        steps: &[hardened(84), PathStep::Coin, hardened(0), CHANGE, ADDRESS],
+        script_type: ScriptType::P2wpkh,
+    };
+
+    /// Metamask, every account is an address index of `m/44'/coin'/0'/0`.
+    pub const METAMASK: WalletPreset = WalletPreset {
+        name: "Metamask",
+        seed: SeedScheme::Bip39,
+        steps: &[hardened(44), PathStep::Coin, hardened(0), fixed(0), ADDRESS],
+        script_type: ScriptType::Evm,
+    };
+
+    /// Samourai deposit accounts, the BIP84 layout of
+    /// [`WalletPreset::LEDGER_LIVE`].
+    pub const SAMOURAI: WalletPreset = WalletPreset {
+        name: "Samourai",
+        seed: SeedScheme::Bip39,
+        steps: BIP84_STEPS,
+        script_type: ScriptType::P2wpkh,
+    };
+
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The seed the master key of the wallet is built from.
+    #[must_use]
+    pub fn seed(&self) -> SeedScheme {
+        self.seed
+    }
+
+    /// Whether both presets derive the same addresses, e.g. wallets sharing
+    /// the BIP84 layout.
+    #[must_use]
+    pub fn is_same_layout(&self, other: &WalletPreset) -> bool {
+        self.seed == other.seed
+            && self.steps == other.steps
+            && self.script_type == other.script_type
+    }
+
+    /// The derivation path template, from the master key to the address.
+    #[must_use]
+    pub fn steps(&self) -> &'static [PathStep] {
+        self.steps
+    }
+
+    #[must_use]
+    pub fn script_type(&self) -> ScriptType {
+        self.script_type
+    }
+
+    #[must_use]
+    pub fn is_hardened_addresses(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|step| matches!(step, PathStep::Address { hardened: true }))
+    }
+
+    /// Fills the path template for an address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wallet has no account or change level for a
+    /// non-default value, the chain is invalid or an index is out of range.
+    pub fn path(
+        &self,
+        coin: &Coin,
+        account: u32,
+        chain: Chain,
+        index: u32,
+        network: Network,
+    ) -> Result<DerivationPath> {
+        chain.child_number()?;
+
+        let mut has_account = false;
+        let mut has_change = false;
+
+        let children = self
+            .steps
+            .iter()
+            .map(|step| match *step {
+                PathStep::Fixed { index, hardened } => ChildNumber::new(index, hardened),
+                PathStep::Coin => {
+                    // EVM test chains keep the coin type
+                    let coin_type = match self.script_type {
+                        ScriptType::Evm => coin.coin_type(),
+                        _ => network.coin_type(coin.coin_type()),
+                    };
+
+                    ChildNumber::new(coin_type, true)
+                }
+                PathStep::Account => {
+                    has_account = true;
+                    ChildNumber::new(account, true)
+                }
+                PathStep::Change { hardened } => {
+                    has_change = true;
+                    ChildNumber::new(chain.index(), hardened)
+                }
+                PathStep::Address { hardened } => ChildNumber::new(index, hardened),
+            })
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+
+        if !has_account && account != 0 {
+            return Err(WalletBipError::InvalidPath(format!(
+                "{} wallets have no account level",
+                self.name
+            )));
+        }
+
+        if !has_change && chain != Chain::External {
+            return Err(WalletBipError::InvalidPath(format!(
+                "{} wallets have no change chain",
+                self.name
+            )));
+        }
+
+        Ok(DerivationPath::new(children))
+    }
+
+    /// Derives the address of a wallet from its master key, built from the
+    /// [`WalletPreset::seed`] of the wallet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not a master key, the coin has no
+    /// addresses of the script type, the path cannot be filled or the
+    /// derivation fails.
+    pub fn derive_address(
+        &self,
+        root: &ExtendedPrivKey,
+        coin: &Coin,
+        account: u32,
+        chain: Chain,
+        index: u32,
+    ) -> Result<Address> {
+        check_depth(root.depth(), ROOT_KEY_DEPTH)?;
+        coin.check_script_type(self.script_type)?;
+
+        let path = self.path(coin, account, chain, index, root.network())?;
+        let privkey = root.derive_path(&path)?;
+
+        Address::from_keys(
+            privkey.as_xprv().private_key().verifying_key(),
+            privkey.as_xprv().private_key(),
+            self.script_type,
+            coin.address_params(root.network()),
+        )
+    }
+
+    /// Derives the addresses of a range of indices of a wallet lazily, see
+    /// [`Client::derive_addresses`](crate::hd_wallet::Client::derive_addresses).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not a master key, the coin has no
+    /// addresses of the script type, the path cannot be filled or the
+    /// derivation fails.
+    pub fn derive_addresses(
+        &self,
+        root: &ExtendedPrivKey,
+        coin: &Coin,
+        account: u32,
+        chain: Chain,
+        indexes: Range<u32>,
+    ) -> Result<Addresses> {
+        check_depth(root.depth(), ROOT_KEY_DEPTH)?;
+        coin.check_script_type(self.script_type)?;
+
+        let path = self.path(coin, account, chain, 0, root.network())?;
+        let chain = DerivationPath::new(path.as_ref()[..path.len() - 1].to_vec());
+
+        Ok(Addresses::new(
+            &root
+                .derive_path(&chain)?
+                .with_address_params(coin.address_params(root.network())),
+            indexes,
+            self.is_hardened_addresses(),
+            self.script_type,
+        ))
+    }
+}
+
+/// Every known wallet, in a stable order.
+///
+/// Several wallets share a layout, e.g. Ledger Live, Trezor and Samourai all
+/// derive BIP84 accounts, see [`WalletPreset::is_same_layout`]. Multibit
+/// Classic has no entry, its wallets hold random keys that no derivation
+/// path reproduces.
+pub const WALLET_PRESETS: &[WalletPreset] = &[
+    WalletPreset::BITCOIN_CORE,
+    WalletPreset::MULTIBIT_HD,
+    WalletPreset::ELECTRUM,
+    WalletPreset::ELECTRUM_SEGWIT,
+    WalletPreset::LEDGER_LIVE,
+    WalletPreset::LEDGER_LEGACY_ETHEREUM,
+    WalletPreset::TREZOR,
+    WalletPreset::MYCELIUM,
+    WalletPreset::COINOMI,
+    WalletPreset::EXODUS,
+    WalletPreset::METAMASK,
+    WalletPreset::SAMOURAI,
+];
+
+impl fmt::Display for PathStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, hardened) = match *self {
+            PathStep::Fixed { index, hardened } => {
+                write!(f, "{index}")?;
+                ("", hardened)
+            }
+            PathStep::Coin => ("coin", true),
+            PathStep::Account => ("account", true),
+            PathStep::Change { hardened } => ("change", hardened),
+            PathStep::Address { hardened } => ("index", hardened),
+        };
+
+        write!(f, "{name}")?;
+
+        if hardened {
+            write!(f, "'")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays the path template, e.g. `m/84'/coin'/account'/change/index`.
+impl fmt::Display for WalletPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+
+        for step in self.steps {
+            write!(f, "/{step}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const ROOT: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    #[rstest]
+    #[case(WalletPreset::BITCOIN_CORE, "m/0'/change'/index'")]
+    #[case(WalletPreset::ELECTRUM, "m/change/index")]
+    #[case(WalletPreset::LEDGER_LEGACY_ETHEREUM, "m/44'/coin'/0'/index")]
+    #[case(WalletPreset::METAMASK, "m/44'/coin'/0'/0/index")]
+    #[case(WalletPreset::SAMOURAI, "m/84'/coin'/account'/change/index")]
+    fn test_template(#[case] preset: WalletPreset, #[case] template: &str) {
+        assert_eq!(preset.to_string(), template);
+    }
+
+    #[rstest]
+    #[case(
+        WalletPreset::BITCOIN_CORE,
+        0,
+        Chain::External,
+        0,
+        "16fWWdLokmpctATuim8q5SvAu1GR9prV5m"
+    )]
+    #[case(
+        WalletPreset::BITCOIN_CORE,
+        0,
+        Chain::Internal,
+        1,
+        "1C2J2rNFRpbGVLtmQ39LdMgvmYkB1o7Kdo"
+    )]
+    #[case(
+        WalletPreset::MULTIBIT_HD,
+        0,
+        Chain::External,
+        0,
+        "17871ErDqdevLTLWBH6WzjUc1EKGDQzCMA"
+    )]
+    #[case(
+        WalletPreset::MYCELIUM,
+        0,
+        Chain::External,
+        0,
+        "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"
+    )]
+    #[case(
+        WalletPreset::TREZOR,
+        0,
+        Chain::External,
+        0,
+        "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+    )]
+    #[case(
+        WalletPreset::LEDGER_LIVE,
+        1,
+        Chain::External,
+        0,
+        "bc1qku0qh0mc00y8tk0n65x2tqw4trlspak0fnjmfz"
+    )]
+    fn test_derive_address(
+        #[case] preset: WalletPreset,
+        #[case] account: u32,
+        #[case] chain: Chain,
+        #[case] index: u32,
+        #[case] hash: &str,
+    ) {
+        let root = ExtendedPrivKey::parse(ROOT, Network::Bitcoin).unwrap();
+
+        let address = preset
+            .derive_address(&root, &Coin::BTC, account, chain, index)
+            .unwrap();
+
+        assert_eq!(address.hash(), hash);
+
+        let batch = preset
+            .derive_addresses(&root, &Coin::BTC, account, chain, index..index + 1)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(batch.hash(), hash);
+    }
+
+    #[rstest]
+    #[case(
+        WalletPreset::ELECTRUM,
+        "cycle rocket west magnet parrot shuffle foot correct salt library feed song",
+        Chain::External,
+        "1NNkttn1YvVGdqBW4PR6zvc3Zx3H5owKRf"
+    )]
+    #[case(
+        WalletPreset::ELECTRUM,
+        "cycle rocket west magnet parrot shuffle foot correct salt library feed song",
+        Chain::Internal,
+        "1KSezYMhAJMWqFbVFB2JshYg69UpmEXR4D"
+    )]
+    #[case(
+        WalletPreset::ELECTRUM_SEGWIT,
+        "bitter grass shiver impose acquire brush forget axis eager alone wine silver",
+        Chain::External,
+        "bc1q3g5tmkmlvxryhh843v4dz026avatc0zzr6h3af"
+    )]
+    #[case(
+        WalletPreset::ELECTRUM_SEGWIT,
+        "bitter grass shiver impose acquire brush forget axis eager alone wine silver",
+        Chain::Internal,
+        "bc1qdy94n2q5qcp0kg7v9yzwe6wvfkhnvyzje7nx2p"
+    )]
+    fn test_derive_electrum_address(
+        #[case] preset: WalletPreset,
+        #[case] mnemonic: &str,
+        #[case] chain: Chain,
+        #[case] hash: &str,
+    ) {
+        let mnemonic = mnemonic.split(' ').collect::<Vec<_>>();
+        let seed = crate::hd_wallet::prepare_electrum_seed(&mnemonic, "").unwrap();
+        let root = crate::hd_wallet::prepare_root(seed, Network::Bitcoin).unwrap();
+        let root = ExtendedPrivKey::parse(&root, Network::Bitcoin).unwrap();
+
+        assert!(matches!(preset.seed(), SeedScheme::Electrum(_)));
+
+        let address = preset
+            .derive_address(&root, &Coin::BTC, 0, chain, 0)
+            .unwrap();
+
+        assert_eq!(address.hash(), hash);
+    }
+
+    #[test]
+    fn test_metamask() {
+        let root = ExtendedPrivKey::parse(ROOT, Network::Bitcoin).unwrap();
+
+        let address = WalletPreset::METAMASK
+            .derive_address(&root, &Coin::ETH, 0, Chain::External, 0)
+            .unwrap();
+
+        assert_eq!(address.hash(), "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+    }
+
+    #[rstest]
+    #[case(WalletPreset::METAMASK, 1, Chain::External)]
+    #[case(WalletPreset::LEDGER_LEGACY_ETHEREUM, 0, Chain::Internal)]
+    fn test_missing_level(
+        #[case] preset: WalletPreset,
+        #[case] account: u32,
+        #[case] chain: Chain,
+    ) {
+        assert!(matches!(
+            preset.path(&Coin::ETH, account, chain, 0, Network::Bitcoin),
+            Err(WalletBipError::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_catalog() {
+        for preset in WALLET_PRESETS {
+            assert!(matches!(
+                preset.steps().last(),
+                Some(PathStep::Address { .. })
+            ));
+            assert!(
+                WALLET_PRESETS
+                    .iter()
+                    .filter(|other| other.name() == preset.name())
+                    .count()
+                    == 1
+            );
+        }
+
+        assert!(WalletPreset::TREZOR.is_same_layout(&WalletPreset::LEDGER_LIVE));
+        assert!(WalletPreset::COINOMI.is_same_layout(&WalletPreset::MYCELIUM));
+        assert!(!WalletPreset::EXODUS.is_same_layout(&WalletPreset::LEDGER_LIVE));
+        assert!(WalletPreset::BITCOIN_CORE.is_hardened_addresses());
+        assert!(!WalletPreset::SAMOURAI.is_hardened_addresses());
+    }
+}
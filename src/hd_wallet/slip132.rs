@@ -0,0 +1,314 @@
+use crate::Result;
+use crate::errors::WalletBipError;
+use crate::hd_wallet::{ExtendedPubPrivKey, MultisigScriptType, Network, ScriptType};
+use bip32::{ExtendedKey, Prefix};
+use std::str::FromStr;
+
+/// The script type an extended key version implies (SLIP-132).
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum ExtendedKeyType {
+    /// `xpub`/`xprv`, `tpub`/`tprv`: legacy, taproot or legacy multisig.
+    P2pkh,
+
+    /// `ypub`/`yprv`, `upub`/`uprv`: nested segwit.
+    P2shP2wpkh,
+
+    /// `zpub`/`zprv`, `vpub`/`vprv`: native segwit.
+    P2wpkh,
+
+    /// `Ypub`/`Yprv`, `Upub`/`Uprv`: nested segwit multisig.
+    P2shP2wshMultisig,
+
+    /// `Zpub`/`Zprv`, `Vpub`/`Vprv`: native segwit multisig.
+    P2wshMultisig,
+}
+
+impl From<ScriptType> for ExtendedKeyType {
+    fn from(script_type: ScriptType) -> Self {
+        match script_type {
+            ScriptType::P2pkh | ScriptType::P2tr | ScriptType::Evm => ExtendedKeyType::P2pkh,
+            ScriptType::P2shP2wpkh => ExtendedKeyType::P2shP2wpkh,
+            ScriptType::P2wpkh => ExtendedKeyType::P2wpkh,
+        }
+    }
+}
+
+impl From<MultisigScriptType> for ExtendedKeyType {
+    fn from(script_type: MultisigScriptType) -> Self {
+        match script_type {
+            MultisigScriptType::P2sh => ExtendedKeyType::P2pkh,
+            MultisigScriptType::P2shP2wsh => ExtendedKeyType::P2shP2wshMultisig,
+            MultisigScriptType::P2wsh => ExtendedKeyType::P2wshMultisig,
+        }
+    }
+}
+
+/// A pair of SLIP-132 versions.
+struct Version {
+    key_type: ExtendedKeyType,
+    is_mainnet: bool,
+    pub_prefix: Prefix,
+    priv_prefix: Prefix,
+}
+
+const fn version(
+    key_type: ExtendedKeyType,
+    is_mainnet: bool,
+    pub_prefix: Prefix,
+    priv_prefix: Prefix,
+) -> Version {
+    Version {
+        key_type,
+        is_mainnet,
+        pub_prefix,
+        priv_prefix,
+    }
+}
+
+const VERSIONS: [Version; 10] = [
+    version(ExtendedKeyType::P2pkh, true, Prefix::XPUB, Prefix::XPRV),
+    version(
+        ExtendedKeyType::P2shP2wpkh,
+        true,
+        Prefix::YPUB,
+        Prefix::YPRV,
+    ),
+    version(ExtendedKeyType::P2wpkh, true, Prefix::ZPUB, Prefix::ZPRV),
+    version(
+        ExtendedKeyType::P2shP2wshMultisig,
+        true,
+        Prefix::from_parts_unchecked("Ypub", 0x0295_b43f),
+        Prefix::from_parts_unchecked("Yprv", 0x0295_b005),
+    ),
+    version(
+        ExtendedKeyType::P2wshMultisig,
+        true,
+        Prefix::from_parts_unchecked("Zpub", 0x02aa_7ed3),
+        Prefix::from_parts_unchecked("Zprv", 0x02aa_7a99),
+    ),
+    version(ExtendedKeyType::P2pkh, false, Prefix::TPUB, Prefix::TPRV),
+    version(
+        ExtendedKeyType::P2shP2wpkh,
+        false,
+        Prefix::from_parts_unchecked("upub", 0x044a_5262),
+        Prefix::from_parts_unchecked("uprv", 0x044a_4e28),
+    ),
+    version(
+        ExtendedKeyType::P2wpkh,
+        false,
+        Prefix::from_parts_unchecked("vpub", 0x045f_1cf6),
+        Prefix::from_parts_unchecked("vprv", 0x045f_18bc),
+    ),
+    version(
+        ExtendedKeyType::P2shP2wshMultisig,
+        false,
+        Prefix::from_parts_unchecked("Upub", 0x0242_89ef),
+        Prefix::from_parts_unchecked("Uprv", 0x0242_85b5),
+    ),
+    version(
+        ExtendedKeyType::P2wshMultisig,
+        false,
+        Prefix::from_parts_unchecked("Vpub", 0x0257_5483),
+        Prefix::from_parts_unchecked("Vprv", 0x0257_5048),
+    ),
+];
+
+/// SLIP-132 prefixes of the extended public and private keys of a key type.
+pub(crate) fn prefixes(key_type: ExtendedKeyType, is_mainnet: bool) -> (Prefix, Prefix) {
+    VERSIONS
+        .iter()
+        .find(|version| version.key_type == key_type && version.is_mainnet == is_mainnet)
+        .map(|version| (version.pub_prefix, version.priv_prefix))
+        .expect("every key type has mainnet and testnet versions")
+}
+
+/// Finds the version pair a public or private prefix belongs to.
+fn find(prefix: Prefix) -> Option<&'static Version> {
+    VERSIONS.iter().find(|version| {
+        version.pub_prefix.version() == prefix.version()
+            || version.priv_prefix.version() == prefix.version()
+    })
+}
+
+/// The network a key version belongs to, test networks share their versions
+/// and are reported as [`Network::Testnet`].
+pub(crate) fn version_network(prefix: Prefix) -> Result<Network> {
+    let version = find(prefix).ok_or_else(|| unknown_version(prefix))?;
+
+    Ok(if version.is_mainnet {
+        Network::Bitcoin
+    } else {
+        Network::Testnet
+    })
+}
+
+/// Detects the script type implied by the version of an extended key.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be parsed or its version is not a
+/// SLIP-132 Bitcoin version.
+pub fn detect_key_type(key: &str) -> Result<ExtendedKeyType> {
+    let extended = ExtendedKey::from_str(key)?;
+
+    find(extended.prefix)
+        .map(|version| version.key_type)
+        .ok_or_else(|| unknown_version(extended.prefix))
+}
+
+/// Re-encodes an extended key with the version of another key type, e.g. a
+/// `zpub` as an `xpub`. Private keys stay private.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be parsed, its version is unknown or
+/// does not belong to the network.
+pub fn convert_key(key: &str, key_type: ExtendedKeyType, network: Network) -> Result<String> {
+    let mut extended = ExtendedKey::from_str(key)?;
+
+    network.check_version(extended.prefix)?;
+
+    let (pub_prefix, priv_prefix) = prefixes(key_type, network.is_mainnet());
+
+    extended.prefix = if extended.prefix.is_private() {
+        priv_prefix
+    } else {
+        pub_prefix
+    };
+
+    Ok(extended.to_string())
+}
+
+fn unknown_version(prefix: Prefix) -> WalletBipError {
+    WalletBipError::InvalidKey(format!("{prefix} is not a known Bitcoin key version"))
+}
+
+/// The public prefix paired with a prefix, e.g. `zpub` for `zprv`.
+pub(crate) fn public_prefix(prefix: Prefix) -> Prefix {
+    find(prefix)
+        .map(|version| version.pub_prefix)
+        .expect("extended keys only hold known versions")
+}
+
+impl ExtendedPubPrivKey {
+    /// Re-encodes both keys with the versions of another key type.
+    #[must_use]
+    pub fn convert(self, key_type: ExtendedKeyType) -> Self {
+        let (pub_prefix, priv_prefix) = prefixes(key_type, self.privkey.network().is_mainnet());
+
+        Self {
+            pubkey: self.pubkey.with_prefix(pub_prefix),
+            privkey: self.privkey.with_prefix(priv_prefix),
+            origin: self.origin,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+    const ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    const MULTISIG_XPUB: &str = "xpub6DkFAXWQ2dHxq2vatrt9qyA3bXYU4ToWQwCHbf5XB2mSTexcHZCeKS1VZYcPoBd5X8yVcbXFHJR9R8UCVpt82VX1VhR28mCyxUFL4r6KFrf";
+
+    #[rstest]
+    #[case(XPUB, ExtendedKeyType::P2wpkh, Network::Bitcoin, ZPUB)]
+    #[case(ZPUB, ExtendedKeyType::P2pkh, Network::Bitcoin, XPUB)]
+    #[case(
+        MULTISIG_XPUB,
+        ExtendedKeyType::P2wshMultisig,
+        Network::Bitcoin,
+        "Zpub74Jru6aftwwHxCUCWEvP6DgrfFsdA4U6ZRtQ5i8qJpMcC39yZGv3egBhQfV3MS9pZtH5z8iV5qWkJsK6ESs6mSzt4qvGhzJxPeeVS2e1zUG"
+    )]
+    #[case(
+        "xprv9zktm1yWCFjfcYr7nqM9UqDK3Vhyf15f3iGgoGfuchETardTk1tPmdh1iKBs86XJiatoijkrXoxcKM2pwZwozGuyEkvectBcwU6RKRafo4D",
+        ExtendedKeyType::P2wshMultisig,
+        Network::Bitcoin,
+        "ZprvAqKWVb3n4aNzjiPjQDPNj5k87E38kbkFCCxoHKjDkUpdKEpq1jbo6ssDZS4WgM43mLCQ6Gx6LM4DD5sigBvnjEPqouRuC7HbNeVagXvJcnz"
+    )]
+    #[case(
+        "xpub6DkFAXWQ2dHxnMKoSBogHrw1rgNJKR4umdbnNVNTYeCGcduxWnNUHgGptqEQWPKRmeW4Zn4FHSbLMBKEWYaMDYu47Ytg6DdFnPNt8hwn5mE",
+        ExtendedKeyType::P2shP2wshMultisig,
+        Network::Bitcoin,
+        "Ypub6jUbbRukkGPp4DgJDD4HL2NKkSZ1UPk111mg59XtJRQZHvJ6XqvJzrntik9U4jCFQkgrBqevdKLPMdYZXU9KAGhKpMhW5XujwqiQ7Csmm4Z"
+    )]
+    #[case(
+        "tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M",
+        ExtendedKeyType::P2wpkh,
+        Network::Testnet,
+        "vpub5Y6cjg78GGuNLsaPhmYsiw4gYX3HoQiRBiSwDaBXKUafCt9bNwWQiitDk5VZ5BVxYnQdwoTyXSs2JHRPAgjAvtbBrf8ZhDYe2jWAqvZVnsc"
+    )]
+    fn test_convert_key(
+        #[case] key: &str,
+        #[case] key_type: ExtendedKeyType,
+        #[case] network: Network,
+        #[case] expected: &str,
+    ) {
+        let converted = convert_key(key, key_type, network).unwrap();
+
+        assert_eq!(converted, expected);
+        assert_eq!(detect_key_type(&converted).unwrap(), key_type);
+    }
+
+    #[test]
+    fn test_convert_key_network_mismatch() {
+        assert!(matches!(
+            convert_key(XPUB, ExtendedKeyType::P2wpkh, Network::Testnet),
+            Err(WalletBipError::NetworkMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        // Litecoin Ltub
+        let mut extended = ExtendedKey::from_str(XPUB).unwrap();
+        extended.prefix = Prefix::from_parts_unchecked("Ltub", 0x019d_a462);
+        let ltub = extended.to_string();
+
+        assert!(matches!(
+            detect_key_type(&ltub),
+            Err(WalletBipError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            convert_key(&ltub, ExtendedKeyType::P2pkh, Network::Bitcoin),
+            Err(WalletBipError::InvalidKey(_))
+        ));
+    }
+
+    #[rstest]
+    #[case(XPUB, ExtendedKeyType::P2pkh)]
+    #[case(ZPUB, ExtendedKeyType::P2wpkh)]
+    #[case(
+        "yprvABrGsX5C9jantZVwdwcQhDXkqsu4RoSAZKBwPnLA3uyeVM3C3fvTuqzru4fovMSLqYSqALGe9MBqCf7Pg7Y7CTsjoNnLYg6HxR2Xo44NX7E",
+        ExtendedKeyType::P2shP2wpkh
+    )]
+    fn test_detect_key_type(#[case] key: &str, #[case] key_type: ExtendedKeyType) {
+        assert_eq!(detect_key_type(key).unwrap(), key_type);
+    }
+
+    #[test]
+    fn test_parse_multisig_versions() {
+        let zprv = "ZprvAqKWVb3n4aNzjiPjQDPNj5k87E38kbkFCCxoHKjDkUpdKEpq1jbo6ssDZS4WgM43mLCQ6Gx6LM4DD5sigBvnjEPqouRuC7HbNeVagXvJcnz";
+
+        assert!(Network::Bitcoin.parse_xprv(zprv).is_ok());
+        assert!(Network::Testnet.parse_xprv(zprv).is_err());
+    }
+
+    #[test]
+    fn test_convert_extended_pub_priv_key() {
+        let extended = crate::hd_wallet::derive(
+            "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu",
+            &"m/84'/0'/0'".parse().unwrap(),
+            Network::Bitcoin,
+        )
+        .unwrap()
+        .convert(ExtendedKeyType::P2wpkh);
+
+        assert_eq!(extended.pubkey.to_string(), ZPUB);
+        assert!(extended.origin.is_some());
+    }
+}
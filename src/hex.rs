@@ -1,7 +1,8 @@
+use crate::Result;
+use crate::errors::WalletBipError;
 use core::fmt::Write;
-use eyre::eyre;
 
-pub(super) fn encode(bytes: &[u8], need_prefix: bool) -> Result<String, eyre::Error> {
+pub(super) fn encode(bytes: &[u8], need_prefix: bool) -> Result<String> {
     let len = (bytes.len() + usize::from(need_prefix)) * 2;
 
     let mut s = String::with_capacity(len);
@@ -17,30 +18,52 @@ pub(super) fn encode(bytes: &[u8], need_prefix: bool) -> Result<String, eyre::Er
     Ok(s)
 }
 
-pub(super) fn decode(s: &str) -> Result<Vec<u8>, eyre::Error> {
-    let s = s.strip_prefix("0x").unwrap_or(s);
+/// Decodes a hex string with an optional `0x` prefix, an odd length is
+/// reported as an invalid character at the end of the string.
+pub(super) fn decode(s: &str) -> Result<Vec<u8>> {
+    let prefix = if s.starts_with("0x") { 2 } else { 0 };
+    let s = &s[prefix..];
 
     if !s.len().is_multiple_of(2) {
-        return Err(eyre!("hex string has odd length"));
+        return Err(WalletBipError::InvalidHex {
+            position: prefix + s.len(),
+        });
     }
 
     let mut out = Vec::with_capacity(s.len() / 2);
 
     let bytes = s.as_bytes();
     for i in (0..bytes.len()).step_by(2) {
-        let hi = from_hex_char(bytes[i])?;
-        let lo = from_hex_char(bytes[i + 1])?;
+        let hi = from_hex_char(bytes[i], prefix + i)?;
+        let lo = from_hex_char(bytes[i + 1], prefix + i + 1)?;
         out.push((hi << 4) | lo);
     }
 
     Ok(out)
 }
 
-fn from_hex_char(c: u8) -> Result<u8, eyre::Error> {
+fn from_hex_char(c: u8, position: usize) -> Result<u8> {
     match c {
         b'0'..=b'9' => Ok(c - b'0'),
         b'a'..=b'f' => Ok(c - b'a' + 10),
         b'A'..=b'F' => Ok(c - b'A' + 10),
-        _ => Err(eyre!("invalid hex character")),
+        _ => Err(WalletBipError::InvalidHex { position }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("0x0g", 3)]
+    #[case("abc", 3)]
+    #[case("zz", 0)]
+    fn test_decode_invalid(#[case] s: &str, #[case] expected: usize) {
+        assert!(matches!(
+            decode(s),
+            Err(WalletBipError::InvalidHex { position }) if position == expected
+        ));
     }
 }
@@ -5,6 +5,7 @@ pub mod errors;
 pub mod hd_wallet;
 
 pub mod mnemonic;
+pub mod slip39;
 
 mod hex;
 
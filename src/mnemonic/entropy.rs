@@ -0,0 +1,378 @@
+use super::{is_invalid_word_count, parse_error};
+use crate::errors::WalletBipError;
+use crate::hex;
+use bip39::{Language, Mnemonic};
+
+/// The number of cards of a deck.
+const DECK_SIZE: usize = 52;
+
+/// The ranks of a deck, `10` may also be written `T`.
+const RANKS: [&str; 13] = [
+    "A", "2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K",
+];
+
+/// The suits of a deck: clubs, diamonds, hearts and spades.
+const SUITS: [char; 4] = ['C', 'D', 'H', 'S'];
+
+/// Builds the mnemonic encoding raw entropy of 16 to 32 bytes.
+///
+/// # Errors
+///
+/// Returns an error if the entropy length is not supported by BIP39.
+pub fn from_entropy(entropy: &[u8], lang: Language) -> crate::Result<Vec<&'static str>> {
+    Ok(Mnemonic::from_entropy_in(lang, entropy)?.words().collect())
+}
+
+/// Builds the mnemonic encoding hex entropy, with an optional `0x` prefix.
+///
+/// # Errors
+///
+/// Returns an error if the entropy is not hex or its length is not supported
+/// by BIP39.
+pub fn from_hex_entropy(entropy: &str, lang: Language) -> crate::Result<Vec<&'static str>> {
+    from_entropy(&hex::decode(entropy)?, lang)
+}
+
+/// Recovers the raw entropy a mnemonic encodes.
+///
+/// # Errors
+///
+/// Returns an error if the mnemonic is not a valid BIP39 phrase.
+pub fn to_entropy(mnemonic: &[&str], lang: Language) -> crate::Result<Vec<u8>> {
+    let phrase =
+        Mnemonic::parse_in(lang, mnemonic.join(" ")).map_err(|e| parse_error(mnemonic, e))?;
+
+    Ok(phrase.to_entropy())
+}
+
+/// Recovers the entropy a mnemonic encodes as hex, see [`to_entropy`].
+///
+/// # Errors
+///
+/// Returns an error if the mnemonic is not a valid BIP39 phrase.
+pub fn to_hex_entropy(mnemonic: &[&str], lang: Language) -> crate::Result<String> {
+    hex::encode(&to_entropy(mnemonic, lang)?, false)
+}
+
+/// Entropy collected from a physical source such as dice, coins or a shuffled
+/// deck of cards, so a mnemonic can be generated without trusting the RNG of
+/// the machine.
+///
+/// Every outcome is turned into bits without bias: an outcome uniform among
+/// `n` values gives the bits of the largest power of two below `n` when it
+/// falls in that range, else it is reduced to the remaining values and the
+/// same rule applies. A die roll of 1 to 4 gives two bits, 5 or 6 one bit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CollectedEntropy {
+    bits: Vec<bool>,
+}
+
+impl CollectedEntropy {
+    /// Collects die rolls written as digits from 1 to 6, whitespace is
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a roll is not a digit from 1 to 6.
+    pub fn from_dice(rolls: &str) -> crate::Result<Self> {
+        let mut entropy = Self::default();
+
+        for roll in rolls.chars().filter(|c| !c.is_whitespace()) {
+            match roll.to_digit(10) {
+                Some(value @ 1..=6) => entropy.push(value as usize - 1, 6),
+                _ => {
+                    return Err(WalletBipError::InvalidEntropy(format!(
+                        "{roll:?} is not a die roll"
+                    )));
+                }
+            }
+        }
+
+        Ok(entropy)
+    }
+
+    /// Collects coin flips written as `H`/`T` or `1`/`0`, whitespace is
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a flip is neither heads nor tails.
+    pub fn from_coins(flips: &str) -> crate::Result<Self> {
+        let mut entropy = Self::default();
+
+        for flip in flips.chars().filter(|c| !c.is_whitespace()) {
+            match flip.to_ascii_uppercase() {
+                'H' | '1' => entropy.push(1, 2),
+                'T' | '0' => entropy.push(0, 2),
+                _ => {
+                    return Err(WalletBipError::InvalidEntropy(format!(
+                        "{flip:?} is not a coin flip"
+                    )));
+                }
+            }
+        }
+
+        Ok(entropy)
+    }
+
+    /// Collects the order of a shuffled deck, cards are written as a rank and
+    /// a suit, e.g. `AS 10H TD 7C`, separated by whitespace or commas. The
+    /// deck may be partially dealt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a card is invalid or dealt twice.
+    pub fn from_cards(cards: &str) -> crate::Result<Self> {
+        let mut entropy = Self::default();
+        let mut remaining = (0..DECK_SIZE).collect::<Vec<_>>();
+
+        for card in cards
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|card| !card.is_empty())
+        {
+            let index = card_index(card)?;
+
+            // The position of the card among the cards left, uniform for a
+            // shuffled deck.
+            let position = remaining
+                .iter()
+                .position(|&other| other == index)
+                .ok_or_else(|| {
+                    WalletBipError::InvalidEntropy(format!("{card:?} is dealt twice"))
+                })?;
+
+            entropy.push(position, remaining.len());
+            remaining.remove(position);
+        }
+
+        Ok(entropy)
+    }
+
+    /// The number of bits collected.
+    #[must_use]
+    pub fn bits(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Builds a mnemonic from the first bits collected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the word count is not supported by BIP39 or not
+    /// enough bits were collected for it.
+    pub fn to_mnemonic(
+        &self,
+        word_count: usize,
+        lang: Language,
+    ) -> crate::Result<Vec<&'static str>> {
+        if is_invalid_word_count(word_count) {
+            return Err(bip39::Error::BadWordCount(word_count).into());
+        }
+
+        // 11 bits per word, one in 33 is checksum
+        let needed = word_count * 32 / 3;
+
+        if self.bits.len() < needed {
+            return Err(WalletBipError::InsufficientEntropy {
+                needed,
+                collected: self.bits.len(),
+            });
+        }
+
+        let entropy = self.bits[..needed]
+            .chunks(8)
+            .map(|byte| {
+                byte.iter()
+                    .fold(0u8, |acc, &bit| (acc << 1) | u8::from(bit))
+            })
+            .collect::<Vec<_>>();
+
+        from_entropy(&entropy, lang)
+    }
+
+    /// Appends the bits of an outcome uniform among `range` values.
+    fn push(&mut self, mut value: usize, mut range: usize) {
+        while range > 1 {
+            let width = range.ilog2();
+            let power = 1 << width;
+
+            if value < power {
+                self.bits
+                    .extend((0..width).rev().map(|bit| (value >> bit) & 1 == 1));
+                return;
+            }
+
+            value -= power;
+            range -= power;
+        }
+    }
+}
+
+/// The index of a card in a deck sorted by suit then rank.
+fn card_index(card: &str) -> crate::Result<usize> {
+    let invalid = || WalletBipError::InvalidEntropy(format!("{card:?} is not a card"));
+
+    let upper = card.to_ascii_uppercase();
+    let suit = upper.chars().last().ok_or_else(invalid)?;
+    let rank = match &upper[..upper.len() - suit.len_utf8()] {
+        "10" => "T",
+        rank => rank,
+    };
+
+    let suit = SUITS.iter().position(|&s| s == suit).ok_or_else(invalid)?;
+    let rank = RANKS.iter().position(|&r| r == rank).ok_or_else(invalid)?;
+
+    Ok(suit * RANKS.len() + rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const ZEROS: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    const ONES: &str = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong";
+
+    #[rstest]
+    #[case("00000000000000000000000000000000", ZEROS)]
+    #[case(
+        "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+        "legal winner thank year wave sausage worth useful legal winner thank yellow"
+    )]
+    #[case(
+        "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote"
+    )]
+    fn test_hex_entropy(#[case] entropy: &str, #[case] mnemonic: &str) {
+        let words = from_hex_entropy(entropy, Language::English).unwrap();
+
+        assert_eq!(words.join(" "), mnemonic);
+        assert_eq!(
+            to_hex_entropy(&words, Language::English).unwrap(),
+            entropy.trim_start_matches("0x")
+        );
+    }
+
+    #[test]
+    fn test_entropy_invalid() {
+        assert!(from_entropy(&[0; 15], Language::English).is_err());
+        assert!(matches!(
+            from_hex_entropy("0g", Language::English),
+            Err(WalletBipError::InvalidHex { position: 1 })
+        ));
+        assert!(matches!(
+            to_entropy(&["abandon"; 12], Language::English),
+            Err(WalletBipError::InvalidChecksum(_))
+        ));
+    }
+
+    #[rstest]
+    #[case("1234 56", "0001101101")]
+    #[case("6 5 4", "1011")]
+    fn test_dice_bits(#[case] rolls: &str, #[case] bits: &str) {
+        let entropy = CollectedEntropy::from_dice(rolls).unwrap();
+
+        assert_eq!(
+            entropy
+                .bits
+                .iter()
+                .map(|&bit| if bit { '1' } else { '0' })
+                .collect::<String>(),
+            bits
+        );
+    }
+
+    #[rstest]
+    #[case(CollectedEntropy::from_dice(&"1".repeat(64)), ZEROS)]
+    #[case(CollectedEntropy::from_dice(&"4".repeat(64)), ONES)]
+    #[case(CollectedEntropy::from_coins(&"T".repeat(128)), ZEROS)]
+    #[case(CollectedEntropy::from_coins(&"h1".repeat(64)), ONES)]
+    fn test_to_mnemonic(#[case] entropy: crate::Result<CollectedEntropy>, #[case] mnemonic: &str) {
+        let entropy = entropy.unwrap();
+
+        assert_eq!(entropy.bits(), 128);
+        assert_eq!(
+            entropy
+                .to_mnemonic(12, Language::English)
+                .unwrap()
+                .join(" "),
+            mnemonic
+        );
+    }
+
+    #[test]
+    fn test_to_mnemonic_insufficient() {
+        let entropy = CollectedEntropy::from_dice(&"6".repeat(100)).unwrap();
+
+        assert_eq!(entropy.bits(), 100);
+        assert!(matches!(
+            entropy.to_mnemonic(12, Language::English),
+            Err(WalletBipError::InsufficientEntropy {
+                needed: 128,
+                collected: 100
+            })
+        ));
+        assert!(entropy.to_mnemonic(13, Language::English).is_err());
+    }
+
+    #[test]
+    fn test_cards() {
+        let deck = SUITS
+            .iter()
+            .flat_map(|suit| RANKS.iter().map(move |rank| format!("{rank}{suit}")))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let entropy = CollectedEntropy::from_cards(&deck).unwrap();
+
+        // the sum of floor(log2(n)) for n from 2 to 52
+        assert_eq!(entropy.bits(), 203);
+        assert_eq!(
+            entropy
+                .to_mnemonic(12, Language::English)
+                .unwrap()
+                .join(" "),
+            ZEROS
+        );
+        assert!(matches!(
+            entropy.to_mnemonic(24, Language::English),
+            Err(WalletBipError::InsufficientEntropy {
+                needed: 256,
+                collected: 203
+            })
+        ));
+
+        // KS is the last of 52 cards, 10H the 36th of 51 and TD the 23rd of 50
+        let entropy = CollectedEntropy::from_cards("KS 10h td").unwrap();
+        assert_eq!(
+            entropy.bits,
+            [
+                true, true, false, false, true, true, true, false, true, true, false
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("AS AS")]
+    #[case("AS 10S TS")]
+    #[case("1S")]
+    #[case("AX")]
+    #[case("S")]
+    fn test_cards_invalid(#[case] cards: &str) {
+        assert!(matches!(
+            CollectedEntropy::from_cards(cards),
+            Err(WalletBipError::InvalidEntropy(_))
+        ));
+    }
+
+    #[rstest]
+    #[case("1230")]
+    #[case("7")]
+    fn test_dice_invalid(#[case] rolls: &str) {
+        assert!(matches!(
+            CollectedEntropy::from_dice(rolls),
+            Err(WalletBipError::InvalidEntropy(_))
+        ));
+    }
+}
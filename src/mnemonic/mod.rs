@@ -3,27 +3,61 @@ use bip39::{Language, Mnemonic, rand};
 use rand::seq::SliceRandom;
 use std::collections::HashSet;
 
+mod entropy;
+mod recover;
+
+pub use entropy::{CollectedEntropy, from_entropy, from_hex_entropy, to_entropy, to_hex_entropy};
+pub use recover::{Candidates, MAX_HIDDEN_WORDS, recover, recover_with};
+
 /// The minimum number of words in a mnemonic.
 const MIN_NB_WORDS: usize = 12;
 
 /// The maximum number of words in a mnemonic.
 const MAX_NB_WORDS: usize = 24;
 
+/// The placeholder that replaces hidden words.
+pub const HIDDEN_WORD: &str = "XXXX";
+
+/// Generates a new mnemonic with the given number of words.
+///
+/// # Errors
+///
+/// Returns an error if the word count is not supported by BIP39.
 pub fn generate(word_count: usize, lang: Language) -> crate::Result<Vec<&'static str>> {
     Ok(Mnemonic::generate_in(lang, word_count)?.words().collect())
 }
 
+#[must_use]
 pub fn is_mnemonic(word: &str, lang: Language) -> bool {
     lang.word_list().contains(&word)
 }
 
+/// Reports unknown words and invalid checksums of a mnemonic with their own
+/// error variants.
+pub(crate) fn parse_error(mnemonic: &[&str], e: bip39::Error) -> WalletBipError {
+    match e {
+        bip39::Error::UnknownWord(index) => WalletBipError::UnknownWord {
+            index,
+            word: mnemonic.get(index).copied().unwrap_or_default().to_string(),
+        },
+        bip39::Error::InvalidChecksum => {
+            WalletBipError::InvalidChecksum("BIP39 mnemonic".to_string())
+        }
+        e => WalletBipError::from(e),
+    }
+}
+
 fn is_invalid_word_count(word_count: usize) -> bool {
     word_count < MIN_NB_WORDS || !word_count.is_multiple_of(3) || word_count > MAX_NB_WORDS
 }
 
+/// Hides [`MAX_HIDDEN_WORDS`] random words of the mnemonic behind a
+/// placeholder, few enough for [`recover`] to find them again.
+///
+/// # Errors
+///
+/// Returns an error if the word count is not supported by BIP39.
 pub fn split<'a>(mnemonic: &[&'a str]) -> crate::Result<Vec<&'a str>> {
-    static HIDED: &str = "XXXX";
-
     if is_invalid_word_count(mnemonic.len()) {
         return Err(WalletBipError::SplitMnemonic(
             "invalid word count".to_string(),
@@ -33,13 +67,19 @@ pub fn split<'a>(mnemonic: &[&'a str]) -> crate::Result<Vec<&'a str>> {
     let mut values = (0..mnemonic.len()).collect::<Vec<_>>();
     values.shuffle(&mut rand::thread_rng());
 
-    values.truncate(mnemonic.len() / 3);
+    values.truncate(MAX_HIDDEN_WORDS);
     let values = values.into_iter().collect::<HashSet<_>>();
 
     Ok(mnemonic
         .iter()
         .enumerate()
-        .map(|(idx, &word)| if values.contains(&idx) { HIDED } else { word })
+        .map(|(idx, &word)| {
+            if values.contains(&idx) {
+                HIDDEN_WORD
+            } else {
+                word
+            }
+        })
         .collect())
 }
 
@@ -73,11 +113,25 @@ mod tests {
     fn test_split_mnemonic() {
         let mnemonic = generate(12, Language::English).unwrap();
 
-        let result1 = split(&mnemonic).unwrap();
-        let result2 = split(&mnemonic).unwrap();
+        let result = split(&mnemonic).unwrap();
 
-        assert_eq!(result1.len(), 12);
-        assert_eq!(result2.len(), 12);
-        assert_ne!(result1, result2);
+        assert_eq!(result.len(), 12);
+        assert_eq!(
+            result.iter().filter(|&&word| word == HIDDEN_WORD).count(),
+            MAX_HIDDEN_WORDS
+        );
+        assert!(
+            result
+                .iter()
+                .zip(&mnemonic)
+                .all(|(hidden, word)| *hidden == HIDDEN_WORD || hidden == word)
+        );
+
+        // 66 ways to hide 2 of 12 words, 20 identical cards are out of reach
+        let results = (0..20)
+            .map(|_| split(&mnemonic).unwrap())
+            .collect::<HashSet<_>>();
+
+        assert!(results.len() > 1);
     }
 }
@@ -0,0 +1,361 @@
+use super::{HIDDEN_WORD, is_invalid_word_count};
+use crate::errors::WalletBipError;
+use crate::hd_wallet::{Network, prepare_root, prepare_seed};
+use bip39::Language;
+use sha2::{Digest, Sha256};
+
+/// The number of bits encoded by a single word.
+const BITS_PER_WORD: usize = 11;
+
+/// The maximum number of hidden words that can be recovered, the number of
+/// words [`super::split`] hides.
+///
+/// Every hidden word multiplies the candidates by 2048. Two hidden words give
+/// 4.2 million phrases to check, of which 1 in 16 (12 words) to 1 in 256 (24
+/// words) pass the checksum and cost a seed derivation in [`recover_with`].
+/// Three hidden words would already give over eight billion phrases.
+pub const MAX_HIDDEN_WORDS: usize = 2;
+
+/// Recovers the words hidden by [`super::split`].
+///
+/// Every hidden position is filled with each word of the list and only the
+/// phrases with a valid BIP39 checksum are yielded. The candidates are
+/// enumerated lazily, the caller can stop at any of them.
+///
+/// # Errors
+///
+/// Returns an error if the word count is invalid, nothing or more than
+/// [`MAX_HIDDEN_WORDS`] words are hidden or a visible word does not belong
+/// to the given language.
+pub fn recover(mnemonic: &[&str], lang: Language) -> crate::Result<Candidates> {
+    Candidates::new(mnemonic, lang)
+}
+
+/// Recovers the words hidden by [`super::split`] and narrows the candidates
+/// down with a known piece of the wallet.
+///
+/// For every phrase with a valid checksum the root extended private key of
+/// the network is built with [`prepare_seed`] and [`prepare_root`] and handed
+/// to `is_known`, which decides whether it matches the known address or
+/// extended key. The search stops at the first match, `None` is returned if
+/// no candidate matches.
+///
+/// # Errors
+///
+/// Returns an error if the word count is invalid, nothing or more than
+/// [`MAX_HIDDEN_WORDS`] words are hidden, a visible word does not belong to
+/// the given language or `is_known` fails.
+pub fn recover_with<F>(
+    mnemonic: &[&str],
+    lang: Language,
+    passphrase: &str,
+    network: Network,
+    mut is_known: F,
+) -> crate::Result<Option<Vec<&'static str>>>
+where
+    F: FnMut(&str) -> crate::Result<bool>,
+{
+    for words in recover(mnemonic, lang)? {
+        let seed = prepare_seed(&words, passphrase)?;
+        let root = prepare_root(seed, network)?;
+
+        if is_known(&root)? {
+            return Ok(Some(words));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The phrases with a valid checksum for a partially hidden mnemonic, see
+/// [`recover`].
+pub struct Candidates {
+    word_list: &'static [&'static str; 2048],
+    hidden: Vec<usize>,
+    indices: Vec<u16>,
+    exhausted: bool,
+}
+
+impl Candidates {
+    fn new(mnemonic: &[&str], lang: Language) -> crate::Result<Self> {
+        if is_invalid_word_count(mnemonic.len()) {
+            return Err(WalletBipError::RecoverMnemonic(
+                "invalid word count".to_string(),
+            ));
+        }
+
+        let mut hidden = Vec::new();
+        let mut indices = Vec::with_capacity(mnemonic.len());
+
+        for (position, &word) in mnemonic.iter().enumerate() {
+            if word == HIDDEN_WORD {
+                hidden.push(position);
+                indices.push(0);
+            } else {
+                let index = lang
+                    .find_word(word)
+                    .ok_or_else(|| WalletBipError::UnknownWord {
+                        index: position,
+                        word: word.to_string(),
+                    })?;
+                indices.push(index);
+            }
+        }
+
+        if hidden.is_empty() {
+            return Err(WalletBipError::RecoverMnemonic(
+                "there are no hidden words".to_string(),
+            ));
+        }
+
+        if hidden.len() > MAX_HIDDEN_WORDS {
+            return Err(WalletBipError::TooManyHiddenWords {
+                hidden: hidden.len(),
+                max: MAX_HIDDEN_WORDS,
+            });
+        }
+
+        Ok(Self {
+            word_list: lang.word_list(),
+            hidden,
+            indices,
+            exhausted: false,
+        })
+    }
+
+    /// Moves to the next combination of the hidden words, the last hidden
+    /// position changes the fastest.
+    fn advance(&mut self) {
+        for &position in self.hidden.iter().rev() {
+            if usize::from(self.indices[position]) + 1 < self.word_list.len() {
+                self.indices[position] += 1;
+                return;
+            }
+
+            self.indices[position] = 0;
+        }
+
+        self.exhausted = true;
+    }
+}
+
+impl Iterator for Candidates {
+    type Item = Vec<&'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.exhausted {
+            let is_valid = is_valid_checksum(&self.indices);
+            let words = is_valid.then(|| {
+                self.indices
+                    .iter()
+                    .map(|&index| self.word_list[usize::from(index)])
+                    .collect()
+            });
+
+            self.advance();
+
+            if words.is_some() {
+                return words;
+            }
+        }
+
+        None
+    }
+}
+
+fn is_valid_checksum(indices: &[u16]) -> bool {
+    let total_bits = indices.len() * BITS_PER_WORD;
+    let checksum_bits = total_bits / 33;
+    let entropy_bits = total_bits - checksum_bits;
+
+    let mut bytes = [0u8; 33];
+
+    for (i, &index) in indices.iter().enumerate() {
+        for bit in 0..BITS_PER_WORD {
+            if index & (1 << (BITS_PER_WORD - 1 - bit)) != 0 {
+                let position = i * BITS_PER_WORD + bit;
+                bytes[position / 8] |= 1 << (7 - position % 8);
+            }
+        }
+    }
+
+    let hash = Sha256::digest(&bytes[..entropy_bits / 8]);
+    let shift = 8 - checksum_bits;
+
+    hash[0] >> shift == bytes[entropy_bits / 8] >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::Client;
+    use crate::hd_wallet::b44::{Bip44, BlockExplorer};
+    use crate::hd_wallet::{Chain, Coin};
+    use crate::mnemonic::split;
+    use rstest::rstest;
+
+    const MNEMONIC: &str =
+        "dragon elbow sheriff outdoor undo brisk aware raw inform correct lake truly";
+
+    #[test]
+    fn test_recover() {
+        let mnemonic = MNEMONIC.split(' ').collect::<Vec<_>>();
+
+        let mut masked = mnemonic.clone();
+        masked[3] = HIDDEN_WORD;
+
+        let result = recover(&masked, Language::English)
+            .unwrap()
+            .collect::<Vec<_>>();
+
+        assert!(result.contains(&mnemonic));
+        assert!(result.iter().all(|words| {
+            bip39::Mnemonic::parse_in(Language::English, words.join(" ")).is_ok()
+        }));
+    }
+
+    #[test]
+    fn test_recover_last_word() {
+        let mnemonic = MNEMONIC.split(' ').collect::<Vec<_>>();
+
+        let mut masked = mnemonic.clone();
+        masked[11] = HIDDEN_WORD;
+
+        let result = recover(&masked, Language::English)
+            .unwrap()
+            .collect::<Vec<_>>();
+
+        // A 12 word phrase carries a 4 bit checksum.
+        assert_eq!(result.len(), 2048 / 16);
+        assert!(result.contains(&mnemonic));
+    }
+
+    #[test]
+    fn test_recover_with_root() {
+        let mnemonic = MNEMONIC.split(' ').collect::<Vec<_>>();
+        let root = "xprv9s21ZrQH143K44CCrMd3EPxUjKWWQkxGYk94ELXf1Jd7x9rhWi2AovprbPJXZ1Pwgyk1Jr37b2Ca3rPyJQyFSnYs296fPHEnccQ8Rc9AKLz";
+
+        let mut masked = mnemonic.clone();
+        masked[11] = HIDDEN_WORD;
+
+        let result = recover_with(
+            &masked,
+            Language::English,
+            "",
+            Network::Bitcoin,
+            |candidate| Ok(candidate == root),
+        )
+        .unwrap();
+
+        assert_eq!(result, Some(mnemonic));
+    }
+
+    #[test]
+    fn test_recover_with_address() {
+        let mnemonic = MNEMONIC.split(' ').collect::<Vec<_>>();
+
+        let mut masked = mnemonic.clone();
+        masked[5] = HIDDEN_WORD;
+
+        let result = recover_with(&masked, Language::English, "", Network::Bitcoin, |root| {
+            let account =
+                BlockExplorer::prepare_account_extended_key(root, &Coin::BTC, 0, Network::Bitcoin)?;
+            let extended = BlockExplorer::derive_extended_key(&account.privkey, Chain::External)?;
+            let address = BlockExplorer::derive_address(&extended.privkey, 0)?;
+
+            Ok(address.hash == "1P9Qj7dj8kKoZeiHNnG1DLa5rhCbuASSER")
+        })
+        .unwrap();
+
+        assert_eq!(result, Some(mnemonic));
+    }
+
+    #[test]
+    fn test_recover_two_words_lazily() {
+        let mnemonic = MNEMONIC.split(' ').collect::<Vec<_>>();
+
+        let mut masked = mnemonic.clone();
+        masked[0] = HIDDEN_WORD;
+        masked[11] = HIDDEN_WORD;
+
+        // "dragon" is near the start of the list, the phrase is found long
+        // before the 2048^2 combinations are exhausted.
+        let found = recover(&masked, Language::English)
+            .unwrap()
+            .any(|words| words == mnemonic);
+
+        assert!(found);
+    }
+
+    #[test]
+    fn test_recover_with_no_match() {
+        let mut masked = MNEMONIC.split(' ').collect::<Vec<_>>();
+        masked[11] = HIDDEN_WORD;
+
+        let result = recover_with(&masked, Language::English, "", Network::Bitcoin, |_| {
+            Ok(false)
+        })
+        .unwrap();
+
+        assert_eq!(result, None);
+    }
+
+    #[rstest]
+    #[case(12)]
+    #[case(24)]
+    fn test_recover_split_card(#[case] word_count: usize) {
+        // The words are at the start of the list, whichever two words split
+        // hides are found among the first candidates.
+        let mnemonic = bip39::Mnemonic::from_entropy(&vec![0; word_count * 4 / 3])
+            .unwrap()
+            .words()
+            .collect::<Vec<_>>();
+        let masked = split(&mnemonic).unwrap();
+
+        let found = recover(&masked, Language::English)
+            .unwrap()
+            .any(|words| words == mnemonic);
+
+        assert!(found);
+    }
+
+    #[test]
+    fn test_recover_too_many_hidden_words() {
+        let mut masked = MNEMONIC.split(' ').collect::<Vec<_>>();
+        masked[..3].fill(HIDDEN_WORD);
+
+        assert!(matches!(
+            recover(&masked, Language::English),
+            Err(WalletBipError::TooManyHiddenWords {
+                hidden: 3,
+                max: MAX_HIDDEN_WORDS
+            })
+        ));
+    }
+
+    #[test]
+    fn test_recover_unknown_word() {
+        let mut masked = MNEMONIC.split(' ').collect::<Vec<_>>();
+        masked[0] = HIDDEN_WORD;
+        masked[1] = "jak";
+
+        assert!(matches!(
+            recover(&masked, Language::English),
+            Err(WalletBipError::UnknownWord { index: 1, word }) if word == "jak"
+        ));
+    }
+
+    #[test]
+    fn test_recover_nothing_hidden() {
+        let mnemonic = MNEMONIC.split(' ').collect::<Vec<_>>();
+
+        assert!(recover(&mnemonic, Language::English).is_err());
+    }
+
+    #[test]
+    fn test_recover_invalid_word_count() {
+        let masked = [HIDDEN_WORD; 10];
+
+        assert!(recover(&masked, Language::English).is_err());
+    }
+}
@@ -0,0 +1,495 @@
+use crate::errors::WalletBipError;
+use bip39::rand::{self, Rng};
+use shamir::Point;
+use share::{CUSTOMIZATION, Share};
+use std::collections::BTreeMap;
+
+mod shamir;
+mod share;
+mod wordlist;
+
+/// The minimum length of a master secret in bytes.
+const MIN_SECRET_LENGTH: usize = 16;
+
+/// The maximum number of groups and of members in a group.
+const MAX_SHARE_COUNT: u8 = 16;
+
+/// The base number of PBKDF2 iterations of the passphrase encryption.
+const BASE_ITERATION_COUNT: u32 = 10000;
+
+/// The number of rounds of the Feistel network.
+const ROUND_COUNT: u8 = 4;
+
+/// The iteration exponent used by Trezor devices.
+pub const DEFAULT_ITERATION_EXPONENT: u8 = 1;
+
+/// Thresholds of a single group of shares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Group {
+    /// The number of member shares required to restore the group.
+    pub member_threshold: u8,
+
+    /// The number of member shares in the group.
+    pub member_count: u8,
+}
+
+impl Group {
+    #[must_use]
+    pub fn new(member_threshold: u8, member_count: u8) -> Self {
+        Self {
+            member_threshold,
+            member_count,
+        }
+    }
+}
+
+/// Splits a master secret into SLIP-39 shares.
+///
+/// The master secret is encrypted with the passphrase, split into groups
+/// of which `group_threshold` are required, and every group is split into
+/// member shares according to its own threshold. The returned shares are
+/// ordered by group and member index.
+///
+/// The shares are extendable like the ones of current Trezor firmware, more
+/// sets of shares can later be created for the same master secret.
+///
+/// # Errors
+///
+/// Returns an error if the master secret is shorter than 128 bits or has an
+/// odd length, the passphrase is not printable ASCII, the iteration exponent
+/// does not fit in 4 bits or the thresholds are inconsistent.
+pub fn split(
+    master_secret: &[u8],
+    passphrase: &str,
+    group_threshold: u8,
+    groups: &[Group],
+    iteration_exponent: u8,
+) -> crate::Result<Vec<Vec<Vec<&'static str>>>> {
+    if master_secret.len() < MIN_SECRET_LENGTH || !master_secret.len().is_multiple_of(2) {
+        return Err(WalletBipError::Slip39(
+            "master secret must be at least 128 bits and have an even length".to_string(),
+        ));
+    }
+
+    if iteration_exponent > 0xF {
+        return Err(WalletBipError::Slip39(
+            "iteration exponent must fit in 4 bits".to_string(),
+        ));
+    }
+
+    let group_count = u8::try_from(groups.len())
+        .ok()
+        .filter(|&count| count <= MAX_SHARE_COUNT)
+        .ok_or_else(|| WalletBipError::Slip39("too many groups".to_string()))?;
+
+    for group in groups {
+        if group.member_count > MAX_SHARE_COUNT {
+            return Err(WalletBipError::Slip39("too many member shares".to_string()));
+        }
+
+        if group.member_threshold == 1 && group.member_count > 1 {
+            return Err(WalletBipError::Slip39(
+                "a group with a threshold of 1 must have a single member".to_string(),
+            ));
+        }
+    }
+
+    let identifier = rand::thread_rng().gen_range(0..1 << 15);
+
+    let encrypted = encrypt(
+        master_secret,
+        passphrase,
+        iteration_exponent,
+        identifier,
+        true,
+    )?;
+
+    let group_points = shamir::split_secret(group_threshold, group_count, &encrypted)?;
+
+    group_points
+        .iter()
+        .zip(groups)
+        .map(|(group_point, group)| {
+            let member_points =
+                shamir::split_secret(group.member_threshold, group.member_count, &group_point.y)?;
+
+            Ok(member_points
+                .into_iter()
+                .map(|member_point| {
+                    Share {
+                        identifier,
+                        extendable: true,
+                        iteration_exponent,
+                        group_index: group_point.x,
+                        group_threshold,
+                        group_count,
+                        member_index: member_point.x,
+                        member_threshold: group.member_threshold,
+                        value: member_point.y,
+                    }
+                    .to_words()
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Combines SLIP-39 shares back into the master secret.
+///
+/// The result is a seed that can be passed to
+/// [`crate::hd_wallet::prepare_root`].
+///
+/// # Errors
+///
+/// Returns an error if a share is malformed or has an invalid checksum, the
+/// shares belong to different secrets, two different shares have the same
+/// index, there are not enough shares to meet the thresholds or the restored
+/// secret does not match its digest.
+pub fn combine<'a, S: AsRef<[&'a str]>>(shares: &[S], passphrase: &str) -> crate::Result<Vec<u8>> {
+    let shares = shares
+        .iter()
+        .map(|words| Share::from_words(words.as_ref()))
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    let first = shares
+        .first()
+        .ok_or_else(|| WalletBipError::Slip39("no shares provided".to_string()))?;
+
+    let mut groups = BTreeMap::<u8, Vec<&Share>>::new();
+
+    for share in &shares {
+        if share.identifier != first.identifier
+            || share.extendable != first.extendable
+            || share.iteration_exponent != first.iteration_exponent
+            || share.group_threshold != first.group_threshold
+            || share.group_count != first.group_count
+            || share.value.len() != first.value.len()
+        {
+            return Err(WalletBipError::Slip39(
+                "shares do not belong to the same secret".to_string(),
+            ));
+        }
+
+        groups.entry(share.group_index).or_default().push(share);
+    }
+
+    let mut group_points = Vec::with_capacity(groups.len());
+
+    for (&group_index, members) in &groups {
+        let member_threshold = members[0].member_threshold;
+
+        if members
+            .iter()
+            .any(|member| member.member_threshold != member_threshold)
+        {
+            return Err(WalletBipError::Slip39(
+                "shares of a group have different thresholds".to_string(),
+            ));
+        }
+
+        let mut points = Vec::<Point>::with_capacity(members.len());
+
+        for member in members {
+            match points.iter().find(|point| point.x == member.member_index) {
+                // The same share entered twice.
+                Some(point) if point.y == member.value => {}
+                Some(_) => {
+                    return Err(WalletBipError::Slip39(format!(
+                        "different shares have the same member index {} in group {group_index}",
+                        member.member_index
+                    )));
+                }
+                None => points.push(Point {
+                    x: member.member_index,
+                    y: member.value.clone(),
+                }),
+            }
+        }
+
+        if points.len() < usize::from(member_threshold) {
+            continue;
+        }
+
+        points.truncate(usize::from(member_threshold));
+
+        group_points.push(Point {
+            x: group_index,
+            y: shamir::recover_secret(member_threshold, &points)?,
+        });
+    }
+
+    if group_points.len() < usize::from(first.group_threshold) {
+        return Err(WalletBipError::Slip39(
+            "not enough shares to restore the secret".to_string(),
+        ));
+    }
+
+    group_points.truncate(usize::from(first.group_threshold));
+
+    let encrypted = shamir::recover_secret(first.group_threshold, &group_points)?;
+
+    decrypt(
+        &encrypted,
+        passphrase,
+        first.iteration_exponent,
+        first.identifier,
+        first.extendable,
+    )
+}
+
+fn encrypt(
+    master_secret: &[u8],
+    passphrase: &str,
+    iteration_exponent: u8,
+    identifier: u16,
+    extendable: bool,
+) -> crate::Result<Vec<u8>> {
+    feistel(
+        master_secret,
+        passphrase,
+        iteration_exponent,
+        identifier,
+        extendable,
+        false,
+    )
+}
+
+fn decrypt(
+    encrypted: &[u8],
+    passphrase: &str,
+    iteration_exponent: u8,
+    identifier: u16,
+    extendable: bool,
+) -> crate::Result<Vec<u8>> {
+    feistel(
+        encrypted,
+        passphrase,
+        iteration_exponent,
+        identifier,
+        extendable,
+        true,
+    )
+}
+
+fn feistel(
+    data: &[u8],
+    passphrase: &str,
+    iteration_exponent: u8,
+    identifier: u16,
+    extendable: bool,
+    reverse: bool,
+) -> crate::Result<Vec<u8>> {
+    if !passphrase.bytes().all(|c| (32..=126).contains(&c)) {
+        return Err(WalletBipError::Slip39(
+            "passphrase must consist of printable ASCII characters".to_string(),
+        ));
+    }
+
+    let mut salt = Vec::new();
+    if !extendable {
+        salt.extend_from_slice(CUSTOMIZATION);
+        salt.extend_from_slice(&identifier.to_be_bytes());
+    }
+
+    let iterations = (BASE_ITERATION_COUNT << iteration_exponent) / u32::from(ROUND_COUNT);
+
+    let (left, right) = data.split_at(data.len() / 2);
+    let (mut left, mut right) = (left.to_vec(), right.to_vec());
+
+    let mut rounds = (0..ROUND_COUNT).collect::<Vec<_>>();
+    if reverse {
+        rounds.reverse();
+    }
+
+    for round in rounds {
+        let mut password = vec![round];
+        password.extend_from_slice(passphrase.as_bytes());
+
+        let mut round_salt = salt.clone();
+        round_salt.extend_from_slice(&right);
+
+        let mut output = vec![0u8; right.len()];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(&password, &round_salt, iterations, &mut output);
+
+        for (byte, key) in left.iter_mut().zip(&output) {
+            *byte ^= key;
+        }
+
+        std::mem::swap(&mut left, &mut right);
+    }
+
+    right.extend_from_slice(&left);
+
+    Ok(right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_wallet::{Network, prepare_root};
+    use crate::hex;
+
+    fn words(share: &str) -> Vec<&str> {
+        share.split(' ').collect()
+    }
+
+    #[test]
+    fn test_combine_single_share() {
+        let shares = [words(
+            "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard",
+        )];
+
+        let secret = combine(&shares, "TREZOR").unwrap();
+
+        assert_eq!(
+            hex::encode(&secret, false).unwrap(),
+            "bb54aac4b89dc868ba37d9cc21b2cece"
+        );
+    }
+
+    #[test]
+    fn test_combine_two_of_three() {
+        let shares = [
+            words(
+                "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+            ),
+            words(
+                "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+            ),
+        ];
+
+        let secret = combine(&shares, "TREZOR").unwrap();
+
+        assert_eq!(
+            hex::encode(&secret, false).unwrap(),
+            "b43ceb7e57a0ea8766221624d01b0864"
+        );
+    }
+
+    #[test]
+    fn test_combine_invalid_checksum() {
+        let shares = [words(
+            "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney",
+        )];
+
+        assert!(combine(&shares, "TREZOR").is_err());
+    }
+
+    #[test]
+    fn test_combine_not_enough_shares() {
+        let shares = [words(
+            "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+        )];
+
+        assert!(combine(&shares, "TREZOR").is_err());
+    }
+
+    #[test]
+    fn test_split_combine() {
+        let secret = hex::decode("bb54aac4b89dc868ba37d9cc21b2cece").unwrap();
+
+        let groups = split(
+            &secret,
+            "TREZOR",
+            2,
+            &[Group::new(1, 1), Group::new(2, 3), Group::new(3, 5)],
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[1].len(), 3);
+        assert_eq!(groups[2].len(), 5);
+        assert!(groups.iter().flatten().all(|share| share.len() == 20));
+
+        let shares = [
+            groups[1][2].clone(),
+            groups[2][4].clone(),
+            groups[1][0].clone(),
+            groups[2][0].clone(),
+            groups[2][2].clone(),
+        ];
+
+        assert_eq!(combine(&shares, "TREZOR").unwrap(), secret);
+        assert_ne!(combine(&shares, "").unwrap(), secret);
+        assert!(combine(&shares[..4], "TREZOR").is_err());
+    }
+
+    #[test]
+    fn test_split_combine_into_root() {
+        let secret =
+            hex::decode("1c5426d456b59ec7f2831ae9c86b64638c498fc48f269a129236db8461f5adbf")
+                .unwrap();
+
+        let groups = split(&secret, "", 1, &[Group::new(2, 3)], 0).unwrap();
+
+        let restored = combine(&groups[0][1..], "").unwrap();
+
+        assert_eq!(
+            prepare_root(restored, Network::Bitcoin).unwrap(),
+            prepare_root(secret, Network::Bitcoin).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_split_extendable() {
+        let secret = [7u8; 16];
+
+        let groups = split(&secret, "", 1, &[Group::new(1, 1)], 0).unwrap();
+
+        assert!(Share::from_words(&groups[0][0]).unwrap().extendable);
+    }
+
+    fn share(
+        group_index: u8,
+        member_index: u8,
+        member_threshold: u8,
+        value: u8,
+    ) -> Vec<&'static str> {
+        Share {
+            identifier: 42,
+            extendable: true,
+            iteration_exponent: 0,
+            group_index,
+            group_threshold: 1,
+            group_count: 2,
+            member_index,
+            member_threshold,
+            value: vec![value; 16],
+        }
+        .to_words()
+    }
+
+    #[test]
+    fn test_combine_same_member_index() {
+        let shares = [share(0, 3, 2, 1), share(0, 3, 2, 2)];
+
+        assert!(matches!(
+            combine(&shares, ""),
+            Err(WalletBipError::Slip39(message)) if message.contains("same member index")
+        ));
+    }
+
+    #[test]
+    fn test_combine_invalid_indices() {
+        assert!(matches!(
+            combine(&[share(0, 1, 1, 1)], ""),
+            Err(WalletBipError::Slip39(message)) if message.contains("number of members")
+        ));
+        assert!(matches!(
+            combine(&[share(2, 0, 1, 1)], ""),
+            Err(WalletBipError::Slip39(message)) if message.contains("number of groups")
+        ));
+    }
+
+    #[test]
+    fn test_split_invalid_parameters() {
+        let secret = [0u8; 16];
+
+        assert!(split(&secret[..15], "", 1, &[Group::new(1, 1)], 0).is_err());
+        assert!(split(&secret, "", 2, &[Group::new(1, 1)], 0).is_err());
+        assert!(split(&secret, "", 1, &[Group::new(1, 2)], 0).is_err());
+        assert!(split(&secret, "", 1, &[Group::new(3, 2)], 0).is_err());
+        assert!(split(&secret, "", 1, &[Group::new(1, 1)], 16).is_err());
+    }
+}
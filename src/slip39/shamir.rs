@@ -0,0 +1,190 @@
+use crate::errors::WalletBipError;
+use bip39::rand::{self, RngCore};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::LazyLock;
+
+/// The x coordinate of the share holding the digest of the secret.
+const DIGEST_INDEX: u8 = 254;
+
+/// The x coordinate of the share holding the secret itself.
+const SECRET_INDEX: u8 = 255;
+
+/// The number of bytes of the secret digest.
+const DIGEST_LENGTH: usize = 4;
+
+/// Exponent and logarithm tables of GF(256) built with the generator `x + 1`
+/// and reduced by the Rijndael polynomial `x^8 + x^4 + x^3 + x + 1`.
+static TABLES: LazyLock<([u8; 255], [u8; 256])> = LazyLock::new(|| {
+    let mut exp = [0u8; 255];
+    let mut log = [0u8; 256];
+
+    let mut poly: u16 = 1;
+    for (i, value) in exp.iter_mut().enumerate() {
+        *value = u8::try_from(poly).expect("poly is reduced to a byte");
+        log[usize::from(poly)] = u8::try_from(i).expect("index is below 255");
+
+        poly = (poly << 1) ^ poly;
+        if poly & 0x100 != 0 {
+            poly ^= 0x11B;
+        }
+    }
+
+    (exp, log)
+});
+
+#[derive(Clone)]
+pub(super) struct Point {
+    pub(super) x: u8,
+    pub(super) y: Vec<u8>,
+}
+
+/// Splits the secret into `count` points of a random polynomial of degree
+/// `threshold - 1`.
+pub(super) fn split_secret(threshold: u8, count: u8, secret: &[u8]) -> crate::Result<Vec<Point>> {
+    if threshold == 0 || threshold > count {
+        return Err(WalletBipError::Slip39(
+            "threshold must be between 1 and the number of shares".to_string(),
+        ));
+    }
+
+    if threshold == 1 {
+        return Ok((0..count)
+            .map(|x| Point {
+                x,
+                y: secret.to_vec(),
+            })
+            .collect());
+    }
+
+    let mut rng = rand::thread_rng();
+
+    let random_count = threshold - 2;
+
+    let mut points = (0..random_count)
+        .map(|x| {
+            let mut y = vec![0u8; secret.len()];
+            rng.fill_bytes(&mut y);
+
+            Point { x, y }
+        })
+        .collect::<Vec<_>>();
+
+    let mut random_part = vec![0u8; secret.len() - DIGEST_LENGTH];
+    rng.fill_bytes(&mut random_part);
+
+    let mut digest = create_digest(&random_part, secret);
+    digest.extend_from_slice(&random_part);
+
+    let mut base = points.clone();
+    base.push(Point {
+        x: DIGEST_INDEX,
+        y: digest,
+    });
+    base.push(Point {
+        x: SECRET_INDEX,
+        y: secret.to_vec(),
+    });
+
+    for x in random_count..count {
+        points.push(Point {
+            x,
+            y: interpolate(&base, x),
+        });
+    }
+
+    Ok(points)
+}
+
+/// Restores the secret from exactly `threshold` points and checks its digest.
+pub(super) fn recover_secret(threshold: u8, points: &[Point]) -> crate::Result<Vec<u8>> {
+    if threshold == 1 {
+        return points
+            .first()
+            .map(|point| point.y.clone())
+            .ok_or_else(|| WalletBipError::Slip39("no shares provided".to_string()));
+    }
+
+    let secret = interpolate(points, SECRET_INDEX);
+    let digest = interpolate(points, DIGEST_INDEX);
+
+    if digest[..DIGEST_LENGTH] != create_digest(&digest[DIGEST_LENGTH..], &secret) {
+        return Err(WalletBipError::Slip39(
+            "invalid digest of the secret".to_string(),
+        ));
+    }
+
+    Ok(secret)
+}
+
+fn create_digest(random_part: &[u8], secret: &[u8]) -> Vec<u8> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(random_part).expect("HMAC accepts keys of any size");
+    mac.update(secret);
+
+    mac.finalize().into_bytes()[..DIGEST_LENGTH].to_vec()
+}
+
+/// Evaluates at `x` the Lagrange polynomial going through all the points.
+fn interpolate(points: &[Point], x: u8) -> Vec<u8> {
+    if let Some(point) = points.iter().find(|point| point.x == x) {
+        return point.y.clone();
+    }
+
+    let (exp, log) = &*TABLES;
+
+    let log_product: usize = points
+        .iter()
+        .map(|point| usize::from(log[usize::from(point.x ^ x)]))
+        .sum();
+
+    let mut result = vec![0u8; points[0].y.len()];
+
+    for point in points {
+        let others: usize = points
+            .iter()
+            .filter(|other| other.x != point.x)
+            .map(|other| usize::from(log[usize::from(point.x ^ other.x)]))
+            .sum();
+
+        let log_basis = (log_product + 255 * points.len()
+            - usize::from(log[usize::from(point.x ^ x)])
+            - others)
+            % 255;
+
+        for (value, &y) in result.iter_mut().zip(&point.y) {
+            if y != 0 {
+                *value ^= exp[(usize::from(log[usize::from(y)]) + log_basis) % 255];
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_recover_secret() {
+        let secret = b"0123456789abcdef";
+
+        let points = split_secret(3, 5, secret).unwrap();
+        assert_eq!(points.len(), 5);
+
+        let subset = points.into_iter().skip(2).collect::<Vec<_>>();
+
+        assert_eq!(recover_secret(3, &subset).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_recover_secret_invalid_digest() {
+        let secret = b"0123456789abcdef";
+
+        let mut points = split_secret(2, 3, secret).unwrap();
+        points[0].y[0] ^= 1;
+
+        assert!(recover_secret(2, &points[..2]).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+use super::wordlist::WORDLIST;
+use crate::errors::WalletBipError;
+
+/// The number of bits encoded by a single word.
+const RADIX_BITS: usize = 10;
+
+/// The number of words of the RS1024 checksum.
+const CHECKSUM_WORDS: usize = 3;
+
+/// The number of words of the identifier, flags and share parameters.
+const HEADER_WORDS: usize = 4;
+
+/// The minimum number of words in a share.
+const MIN_NB_WORDS: usize = 20;
+
+/// The customization string of shares without the extendable flag.
+pub(super) const CUSTOMIZATION: &[u8] = b"shamir";
+
+/// The customization string of shares with the extendable flag.
+const CUSTOMIZATION_EXTENDABLE: &[u8] = b"shamir_extendable";
+
+/// A single decoded SLIP-39 share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Share {
+    pub(super) identifier: u16,
+    pub(super) extendable: bool,
+    pub(super) iteration_exponent: u8,
+    pub(super) group_index: u8,
+    pub(super) group_threshold: u8,
+    pub(super) group_count: u8,
+    pub(super) member_index: u8,
+    pub(super) member_threshold: u8,
+    pub(super) value: Vec<u8>,
+}
+
+impl Share {
+    pub(super) fn to_words(&self) -> Vec<&'static str> {
+        let value_words = (self.value.len() * 8).div_ceil(RADIX_BITS);
+
+        let mut indices = Vec::with_capacity(HEADER_WORDS + value_words + CHECKSUM_WORDS);
+
+        let header = (u64::from(self.identifier) << 25)
+            | (u64::from(self.extendable) << 24)
+            | (u64::from(self.iteration_exponent) << 20)
+            | (u64::from(self.group_index) << 16)
+            | (u64::from(self.group_threshold - 1) << 12)
+            | (u64::from(self.group_count - 1) << 8)
+            | (u64::from(self.member_index) << 4)
+            | u64::from(self.member_threshold - 1);
+
+        for i in (0..HEADER_WORDS).rev() {
+            indices.push(word_index(header >> (i * RADIX_BITS)));
+        }
+
+        // The value is left padded with zero bits up to a whole number of words.
+        let padding = value_words * RADIX_BITS - self.value.len() * 8;
+
+        let mut accumulator = 0u64;
+        let mut bits = padding;
+
+        for &byte in &self.value {
+            accumulator = (accumulator << 8) | u64::from(byte);
+            bits += 8;
+
+            while bits >= RADIX_BITS {
+                bits -= RADIX_BITS;
+                indices.push(word_index(accumulator >> bits));
+            }
+        }
+
+        indices.extend(create_checksum(self.customization(), &indices));
+
+        indices
+            .into_iter()
+            .map(|index| WORDLIST[usize::from(index)])
+            .collect()
+    }
+
+    pub(super) fn from_words(words: &[&str]) -> crate::Result<Self> {
+        if words.len() < MIN_NB_WORDS {
+            return Err(WalletBipError::Slip39("invalid word count".to_string()));
+        }
+
+        let indices = words
+            .iter()
+            .enumerate()
+            .map(|(position, word)| {
+                WORDLIST
+                    .binary_search(word)
+                    .map(|index| u16::try_from(index).expect("word list has 1024 words"))
+                    .map_err(|_| WalletBipError::UnknownWord {
+                        index: position,
+                        word: (*word).to_string(),
+                    })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let header = indices[..HEADER_WORDS]
+            .iter()
+            .fold(0u64, |acc, &index| (acc << RADIX_BITS) | u64::from(index));
+
+        let extendable = (header >> 24) & 1 == 1;
+
+        let customization = if extendable {
+            CUSTOMIZATION_EXTENDABLE
+        } else {
+            CUSTOMIZATION
+        };
+
+        if polymod(customization, &indices) != 1 {
+            return Err(WalletBipError::InvalidChecksum("SLIP-39 share".to_string()));
+        }
+
+        let value_words = indices.len() - HEADER_WORDS - CHECKSUM_WORDS;
+        let padding = (value_words * RADIX_BITS) % 16;
+
+        if padding > 8 {
+            return Err(WalletBipError::Slip39("invalid padding".to_string()));
+        }
+
+        let bits = indices[HEADER_WORDS..HEADER_WORDS + value_words]
+            .iter()
+            .flat_map(|&index| (0..RADIX_BITS).rev().map(move |i| (index >> i) & 1 == 1))
+            .collect::<Vec<_>>();
+
+        if bits[..padding].iter().any(|&bit| bit) {
+            return Err(WalletBipError::Slip39("invalid padding".to_string()));
+        }
+
+        let value = bits[padding..]
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0u8, |acc, &bit| (acc << 1) | u8::from(bit))
+            })
+            .collect();
+
+        let field = |shift: u32| u8::try_from((header >> shift) & 0xF).expect("masked to 4 bits");
+
+        let share = Self {
+            identifier: u16::try_from(header >> 25).expect("identifier has 15 bits"),
+            extendable,
+            iteration_exponent: field(20),
+            group_index: field(16),
+            group_threshold: field(12) + 1,
+            group_count: field(8) + 1,
+            member_index: field(4),
+            member_threshold: field(0) + 1,
+            value,
+        };
+
+        if share.group_threshold > share.group_count {
+            return Err(WalletBipError::Slip39(
+                "group threshold exceeds the number of groups".to_string(),
+            ));
+        }
+
+        if share.group_index >= share.group_count {
+            return Err(WalletBipError::Slip39(
+                "group index exceeds the number of groups".to_string(),
+            ));
+        }
+
+        // The member count is not encoded, but a group with a threshold of 1
+        // has a single member with the index 0.
+        if share.member_threshold == 1 && share.member_index != 0 {
+            return Err(WalletBipError::Slip39(
+                "member index exceeds the number of members".to_string(),
+            ));
+        }
+
+        Ok(share)
+    }
+
+    fn customization(&self) -> &'static [u8] {
+        if self.extendable {
+            CUSTOMIZATION_EXTENDABLE
+        } else {
+            CUSTOMIZATION
+        }
+    }
+}
+
+fn word_index(value: u64) -> u16 {
+    u16::try_from(value & 0x3FF).expect("masked to 10 bits")
+}
+
+fn polymod(customization: &[u8], values: &[u16]) -> u32 {
+    const GEN: [u32; 10] = [
+        0x00E0_E040,
+        0x01C1_C080,
+        0x0383_8100,
+        0x0707_0200,
+        0x0E0E_0009,
+        0x1C0C_2412,
+        0x3808_6C24,
+        0x3090_FC48,
+        0x21B1_F890,
+        0x03F3_F120,
+    ];
+
+    let mut chk = 1u32;
+
+    for value in customization
+        .iter()
+        .map(|&c| u32::from(c))
+        .chain(values.iter().map(|&v| u32::from(v)))
+    {
+        let b = chk >> 20;
+        chk = ((chk & 0xF_FFFF) << 10) ^ value;
+
+        for (i, generator) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+
+    chk
+}
+
+fn create_checksum(customization: &[u8], indices: &[u16]) -> [u16; CHECKSUM_WORDS] {
+    let mut values = indices.to_vec();
+    values.extend([0; CHECKSUM_WORDS]);
+
+    let polymod = polymod(customization, &values) ^ 1;
+
+    [
+        word_index(u64::from(polymod >> 20)),
+        word_index(u64::from(polymod >> 10)),
+        word_index(u64::from(polymod)),
+    ]
+}